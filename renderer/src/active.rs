@@ -0,0 +1,77 @@
+//! Active cells: a set of cells whose blocks glow, for stepping through an
+//! explanation. The set starts from `--active-cells <file>` and lives in
+//! the [`ActiveCells`] resource, so any system can change it at runtime;
+//! V moves it one cell further along the pipe route.
+
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+
+use crate::{
+    Positions,
+    keymap::{Action, Keymap},
+    overlays::BlockMaterialSet,
+    pipe_path,
+};
+
+pub struct ActivePlugin {
+    pub cells: HashSet<(usize, usize, usize)>,
+    pub color: Color,
+    pub intensity: f32,
+}
+
+impl Plugin for ActivePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ActiveCells {
+            cells: self.cells.clone(),
+        })
+        .insert_resource(ActiveGlow {
+            emissive: self.color.to_linear() * self.intensity,
+            step: None,
+        })
+        .add_systems(Update, step_along_path_system.before(BlockMaterialSet::Overlays));
+    }
+}
+
+/// Cells whose blocks are drawn glowing.
+#[derive(Resource, Default)]
+pub struct ActiveCells {
+    pub cells: HashSet<(usize, usize, usize)>,
+}
+
+#[derive(Resource)]
+pub struct ActiveGlow {
+    emissive: LinearRgba,
+    /// Position along the pipe route reached with V.
+    step: Option<usize>,
+}
+
+impl ActiveGlow {
+    /// Makes an active block's material glow.
+    pub fn apply(&self, material: &mut StandardMaterial) {
+        material.emissive = self.emissive;
+    }
+}
+
+fn step_along_path_system(
+    key_input: Option<Res<ButtonInput<KeyCode>>>,
+    keymap: Res<Keymap>,
+    positions: Res<Positions>,
+    mut glow: ResMut<ActiveGlow>,
+    mut active: ResMut<ActiveCells>,
+) {
+    if !key_input.is_some_and(|key_input| keymap.just_pressed(Action::StepActive, &key_input)) {
+        return;
+    }
+
+    let route = match pipe_path::ordered_path(&positions) {
+        Ok(route) => route,
+        Err(err) => {
+            warn!("Can't step along the pipes: {err}");
+            return;
+        }
+    };
+    let step = glow.step.map_or(0, |step| (step + 1) % route.len());
+    glow.step = Some(step);
+    active.cells = HashSet::from([route[step].cell]);
+}
@@ -0,0 +1,184 @@
+//! `--anaglyph`: red/cyan stereo for viewing the box with 3D glasses. Two
+//! eye cameras follow the main one, `--eye-separation` apart along its
+//! right axis and both aimed at its focus, and render into images that a
+//! full-window HUD panel composites into one picture.
+//!
+//! The red channel takes the left eye's brightness rather than its red, a
+//! half-color anaglyph: a fully red or cyan block would otherwise reach
+//! only one eye and lose its depth. The main camera still draws the scene
+//! under the panel, so picking, the HUD and F12 screenshots keep working
+//! as usual.
+
+use bevy::{
+    asset::load_internal_asset,
+    picking::PickingBehavior,
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_resource::{AsBindGroup, ShaderRef},
+        renderer::RenderAdapter,
+    },
+    window::PrimaryWindow,
+};
+use bevy_panorbit_camera::PanOrbitCamera;
+
+use crate::{Config, camera, offscreen::render_target};
+
+const ANAGLYPH_SHADER: Handle<Shader> = Handle::weak_from_u128(0x91d4_2b7e_06c3_4f58_a1e9_5c07_d86b_3f2a);
+
+pub struct AnaglyphPlugin {
+    /// Distance between the eyes in world units (cells).
+    pub separation: f32,
+}
+
+impl Plugin for AnaglyphPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, ANAGLYPH_SHADER, "shaders/anaglyph.wgsl", Shader::from_wgsl);
+
+        app.insert_resource(Anaglyph {
+            separation: self.separation,
+        })
+        .add_plugins(UiMaterialPlugin::<AnaglyphMaterial>::default())
+        .add_systems(Startup, spawn_eyes)
+        .add_systems(Update, resize_eyes_system)
+        // After the orbit camera has moved for this frame.
+        .add_systems(
+            PostUpdate,
+            follow_camera_system.before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct Anaglyph {
+    separation: f32,
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Clone)]
+struct AnaglyphMaterial {
+    #[texture(0)]
+    #[sampler(1)]
+    left: Handle<Image>,
+    #[texture(2)]
+    #[sampler(3)]
+    right: Handle<Image>,
+}
+
+impl UiMaterial for AnaglyphMaterial {
+    fn fragment_shader() -> ShaderRef {
+        ANAGLYPH_SHADER.into()
+    }
+}
+
+type MainCamera<'a> = (&'a Transform, Ref<'a, Projection>, &'a PanOrbitCamera);
+
+/// An eye camera, offset from the main camera by `side` times half the
+/// separation along its right axis.
+#[derive(Component)]
+struct Eye {
+    side: f32,
+    target: Handle<Image>,
+}
+
+pub fn parse_eye_separation(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(separation) if separation.is_finite() && separation > 0.0 => Ok(separation),
+        _ => Err(format!("expected a positive distance in cells; got `{s}`")),
+    }
+}
+
+fn spawn_eyes(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<AnaglyphMaterial>>,
+    config: Res<Config>,
+    adapter: Res<RenderAdapter>,
+) {
+    // Sized to the window once it is known.
+    let left = render_target(&mut images, 1, 1);
+    let right = render_target(&mut images, 1, 1);
+
+    for (side, target, order) in [(-1.0, &left, -2), (1.0, &right, -1)] {
+        commands.spawn((
+            Camera3d::default(),
+            Camera {
+                order,
+                target: RenderTarget::Image(target.clone()),
+                ..default()
+            },
+            camera::projection(&config),
+            camera::supported_msaa(config.msaa, &adapter),
+            Eye {
+                side,
+                target: target.clone(),
+            },
+        ));
+    }
+
+    // Behind the rest of the HUD.
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        MaterialNode(materials.add(AnaglyphMaterial { left, right })),
+        GlobalZIndex(-1),
+        PickingBehavior::IGNORE,
+    ));
+}
+
+fn follow_camera_system(
+    anaglyph: Res<Anaglyph>,
+    main: Option<Single<MainCamera, Without<Eye>>>,
+    mut eyes: Query<(&Eye, &mut Transform, &mut Projection)>,
+) {
+    let Some(main) = main else {
+        return;
+    };
+    let (main_transform, main_projection, pan_orbit) = main.into_inner();
+
+    for (eye, mut transform, mut projection) in eyes.iter_mut() {
+        let position = main_transform.translation + main_transform.right() * eye.side * anaglyph.separation / 2.0;
+        *transform = Transform::from_translation(position).looking_at(pan_orbit.focus, main_transform.up());
+        if main_projection.is_changed() {
+            *projection = main_projection.clone();
+        }
+    }
+}
+
+/// Keeps the eye images the size of the window, so the composite is as
+/// sharp as the main view. Cameras don't pick up a resized target, so each
+/// eye gets a new image instead.
+fn resize_eyes_system(
+    window: Option<Single<&Window, With<PrimaryWindow>>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<AnaglyphMaterial>>,
+    mut eyes: Query<(&mut Eye, &mut Camera)>,
+    panels: Query<&MaterialNode<AnaglyphMaterial>>,
+) {
+    let Some(window) = window else {
+        return;
+    };
+    let size = window.physical_size().max(UVec2::ONE);
+
+    for (mut eye, mut camera) in eyes.iter_mut() {
+        if images.get(&eye.target).is_none_or(|image| image.size() == size) {
+            continue;
+        }
+        images.remove(&eye.target);
+        eye.target = render_target(&mut images, size.x, size.y);
+        camera.target = RenderTarget::Image(eye.target.clone());
+
+        for panel in panels.iter() {
+            if let Some(material) = materials.get_mut(&panel.0) {
+                if eye.side < 0.0 {
+                    material.left = eye.target.clone();
+                } else {
+                    material.right = eye.target.clone();
+                }
+            }
+        }
+    }
+}
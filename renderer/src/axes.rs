@@ -0,0 +1,123 @@
+//! World axes: X red, Y green and Z blue lines (or the `--axis-colors`) from the origin of the cell
+//! coordinates, each a cell past the far side of the box and labeled with
+//! the direction it points in, to see how the solution's coordinates and
+//! sides map onto the screen. Toggled with E, or on from the start with
+//! `--axes`.
+//!
+//! The origin sits behind and below the box, under the floor, so the axes
+//! are drawn over everything instead of being hidden by it.
+
+use bevy::prelude::*;
+use bevy_panorbit_camera::PanOrbitCamera;
+
+use crate::{
+    Config,
+    keymap::{Action, Keymap},
+};
+
+pub struct AxesPlugin {
+    pub shown: bool,
+}
+
+impl Plugin for AxesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_gizmo_config(
+            AxesGizmos,
+            GizmoConfig {
+                line_width: 3.0,
+                depth_bias: -1.0,
+                ..default()
+            },
+        )
+        .insert_resource(Axes { shown: self.shown })
+        .add_systems(Startup, spawn_axis_labels)
+        .add_systems(
+            Update,
+            (toggle_axes_system, draw_axes_system, place_axis_labels_system).chain(),
+        );
+    }
+}
+
+#[derive(Default, Reflect, GizmoConfigGroup)]
+struct AxesGizmos;
+
+#[derive(Resource)]
+pub struct Axes {
+    pub shown: bool,
+}
+
+/// Label at the tip of an axis.
+#[derive(Component)]
+struct AxisLabel {
+    tip: Vec3,
+}
+
+/// Every axis with its color and label, ending a cell past the box.
+fn axis_lines(config: &Config) -> [(Vec3, Color, &'static str); 3] {
+    let [x, y, z] = config.axis_colors;
+    [
+        (Vec3::X * (config.width + 1) as f32, x, "+X east"),
+        (Vec3::Y * (config.height + 1) as f32, y, "+Y above"),
+        (Vec3::Z * (config.depth + 1) as f32, z, "+Z north"),
+    ]
+}
+
+fn spawn_axis_labels(mut commands: Commands, config: Res<Config>, axes: Res<Axes>) {
+    for (tip, color, name) in axis_lines(&config) {
+        commands.spawn((
+            Text::new(name),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(color),
+            Node {
+                position_type: PositionType::Absolute,
+                display: if axes.shown { Display::Flex } else { Display::None },
+                ..default()
+            },
+            AxisLabel {
+                tip: config.origin + tip,
+            },
+        ));
+    }
+}
+
+fn toggle_axes_system(key_input: Option<Res<ButtonInput<KeyCode>>>, keymap: Res<Keymap>, mut axes: ResMut<Axes>) {
+    if key_input.is_some_and(|key_input| keymap.just_pressed(Action::Axes, &key_input)) {
+        axes.shown = !axes.shown;
+    }
+}
+
+fn draw_axes_system(axes: Res<Axes>, config: Res<Config>, mut gizmos: Gizmos<AxesGizmos>) {
+    if !axes.shown {
+        return;
+    }
+
+    for (tip, color, _) in axis_lines(&config) {
+        gizmos.arrow(config.origin, config.origin + tip, color);
+    }
+}
+
+fn place_axis_labels_system(
+    axes: Res<Axes>,
+    camera_query: Option<Single<(&Camera, &GlobalTransform), With<PanOrbitCamera>>>,
+    mut labels: Query<(&AxisLabel, &mut Node)>,
+) {
+    let Some(camera_query) = camera_query else {
+        return;
+    };
+    let (camera, camera_transform) = *camera_query;
+
+    for (label, mut node) in labels.iter_mut() {
+        match camera.world_to_viewport(camera_transform, label.tip) {
+            Ok(position) if axes.shown => {
+                // Just past the arrowhead.
+                node.left = Val::Px(position.x + 4.0);
+                node.top = Val::Px(position.y);
+                node.display = Display::Flex;
+            }
+            _ => node.display = Display::None,
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! A compact binary encoding of a parsed solution, written with
+//! `--dump-bin` and read back with `--format bin`, so huge solutions load
+//! without re-parsing megabytes of atoms.
+//!
+//! All integers are little-endian. After the magic and a version byte
+//! come the blocks (`u32` count, then per cell its `u32` x, y, z, the id
+//! as a `u16` length and UTF-8 bytes, the `u32` unit and a `u8` count of
+//! quarter turns about each of x, y and z), the pipes (`u32` count of
+//! cells, then per cell its x, y, z, a `u8` segment count and one `u8`
+//! per direction, numbered in `Direction::all` order) and the cells
+//! marked empty (`u32` count, then x, y, z).
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs, io,
+    path::Path,
+};
+
+use crate::{BlockInfo, Direction, Orientation, Positions};
+
+const MAGIC: &[u8; 4] = b"KRRB";
+const VERSION: u8 = 1;
+
+/// Smallest encoding of a block (cell, empty id, unit and turns), a pipe
+/// cell (cell and segment count) and an empty cell.
+const MIN_BLOCK_SIZE: usize = 12 + 2 + 4 + 3;
+const MIN_PIPE_SIZE: usize = 12 + 1;
+const CELL_SIZE: usize = 12;
+
+#[derive(Debug)]
+pub enum BinaryError {
+    Io(io::Error),
+    NotBinary,
+    UnsupportedVersion(u8),
+    Truncated,
+    InvalidId,
+    InvalidDirection(u8),
+    InvalidTurns(u8),
+    TooLarge(&'static str),
+}
+
+impl fmt::Display for BinaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BinaryError::Io(err) => write!(f, "{err}"),
+            BinaryError::NotBinary => write!(f, "not a binary solution (written with --dump-bin)"),
+            BinaryError::UnsupportedVersion(version) => write!(f, "unsupported binary format version {version}"),
+            BinaryError::Truncated => write!(f, "file ends in the middle of the solution"),
+            BinaryError::InvalidId => write!(f, "block id is not valid UTF-8"),
+            BinaryError::InvalidDirection(byte) => write!(f, "invalid direction byte {byte}"),
+            BinaryError::InvalidTurns(turns) => write!(f, "invalid count of quarter turns {turns}"),
+            BinaryError::TooLarge(what) => write!(f, "too many {what} for the binary format"),
+        }
+    }
+}
+
+impl std::error::Error for BinaryError {}
+
+pub fn load(path: &Path) -> Result<Positions, BinaryError> {
+    decode(&fs::read(path).map_err(BinaryError::Io)?)
+}
+
+pub fn save(path: &Path, positions: &Positions) -> Result<(), BinaryError> {
+    fs::write(path, encode(positions)?).map_err(BinaryError::Io)
+}
+
+pub fn encode(positions: &Positions) -> Result<Vec<u8>, BinaryError> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(MAGIC);
+    bytes.push(VERSION);
+
+    put_len(&mut bytes, positions.positions.len(), "blocks")?;
+    for (&cell, block) in &positions.positions {
+        put_cell(&mut bytes, cell)?;
+        let id_len = u16::try_from(block.id.len()).map_err(|_| BinaryError::TooLarge("bytes in a block id"))?;
+        bytes.extend_from_slice(&id_len.to_le_bytes());
+        bytes.extend_from_slice(block.id.as_bytes());
+        bytes.extend_from_slice(&block.unit.to_le_bytes());
+        bytes.extend_from_slice(&block.orientation.0);
+    }
+
+    put_len(&mut bytes, positions.pipe_positions.len(), "pipe cells")?;
+    for (&cell, segments) in &positions.pipe_positions {
+        put_cell(&mut bytes, cell)?;
+        let count = u8::try_from(segments.len()).map_err(|_| BinaryError::TooLarge("segments in a cell"))?;
+        bytes.push(count);
+        for &(in_dir, out_dir) in segments {
+            bytes.push(direction_byte(in_dir));
+            bytes.push(direction_byte(out_dir));
+        }
+    }
+
+    put_len(&mut bytes, positions.empty.len(), "empty cells")?;
+    for &cell in &positions.empty {
+        put_cell(&mut bytes, cell)?;
+    }
+
+    Ok(bytes)
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Positions, BinaryError> {
+    let mut reader = Reader { bytes };
+    if reader.take(MAGIC.len()).ok() != Some(MAGIC.as_slice()) {
+        return Err(BinaryError::NotBinary);
+    }
+    let version = reader.u8()?;
+    if version != VERSION {
+        return Err(BinaryError::UnsupportedVersion(version));
+    }
+
+    let block_count = reader.count(MIN_BLOCK_SIZE)?;
+    let mut positions = HashMap::with_capacity(block_count);
+    for _ in 0..block_count {
+        let cell = reader.cell()?;
+        let id_len = reader.u16()? as usize;
+        let id = std::str::from_utf8(reader.take(id_len)?).map_err(|_| BinaryError::InvalidId)?;
+        let unit = reader.u32()?;
+        let block = BlockInfo {
+            id: id.to_string(),
+            unit,
+            orientation: Orientation([reader.turns()?, reader.turns()?, reader.turns()?]),
+        };
+        positions.insert(cell, block);
+    }
+
+    let pipe_count = reader.count(MIN_PIPE_SIZE)?;
+    let mut pipe_positions = HashMap::with_capacity(pipe_count);
+    for _ in 0..pipe_count {
+        let cell = reader.cell()?;
+        let segment_count = reader.u8()?;
+        let segments = (0..segment_count)
+            .map(|_| Ok((reader.direction()?, reader.direction()?)))
+            .collect::<Result<Vec<_>, BinaryError>>()?;
+        pipe_positions.insert(cell, segments);
+    }
+
+    let empty_count = reader.count(CELL_SIZE)?;
+    let empty = (0..empty_count)
+        .map(|_| reader.cell())
+        .collect::<Result<HashSet<_>, BinaryError>>()?;
+
+    Ok(Positions {
+        positions,
+        pipe_positions,
+        empty,
+    })
+}
+
+fn put_len(bytes: &mut Vec<u8>, len: usize, what: &'static str) -> Result<(), BinaryError> {
+    let len = u32::try_from(len).map_err(|_| BinaryError::TooLarge(what))?;
+    bytes.extend_from_slice(&len.to_le_bytes());
+    Ok(())
+}
+
+fn put_cell(bytes: &mut Vec<u8>, (x, y, z): (usize, usize, usize)) -> Result<(), BinaryError> {
+    for coord in [x, y, z] {
+        let coord = u32::try_from(coord).map_err(|_| BinaryError::TooLarge("cells along an axis"))?;
+        bytes.extend_from_slice(&coord.to_le_bytes());
+    }
+    Ok(())
+}
+
+fn direction_byte(direction: Direction) -> u8 {
+    Direction::all().position(|other| other == direction).unwrap() as u8
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], BinaryError> {
+        if self.bytes.len() < len {
+            return Err(BinaryError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, BinaryError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, BinaryError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, BinaryError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    /// A count of records at least `record_size` bytes long, refused as
+    /// truncated if the rest of the file can't hold that many, so a corrupt
+    /// count doesn't allocate for billions of them.
+    fn count(&mut self, record_size: usize) -> Result<usize, BinaryError> {
+        let count = self.u32()? as usize;
+        if count.saturating_mul(record_size) > self.bytes.len() {
+            return Err(BinaryError::Truncated);
+        }
+        Ok(count)
+    }
+
+    fn cell(&mut self) -> Result<(usize, usize, usize), BinaryError> {
+        Ok((self.u32()? as usize, self.u32()? as usize, self.u32()? as usize))
+    }
+
+    fn turns(&mut self) -> Result<u8, BinaryError> {
+        let turns = self.u8()?;
+        if turns > 3 {
+            return Err(BinaryError::InvalidTurns(turns));
+        }
+        Ok(turns)
+    }
+
+    fn direction(&mut self) -> Result<Direction, BinaryError> {
+        let byte = self.u8()?;
+        Direction::all()
+            .nth(byte.into())
+            .ok_or(BinaryError::InvalidDirection(byte))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_sol;
+
+    const SOLUTION: &str = "block_pos(1,1,1,1,1) block_pos(2,1,1,1,2) block_pos(1,2,1,\"red corner\",1) \
+        rot(x,1,90) rot(z,1,270) pipe_pos(1,1,1,w,e) pipe_pos(2,1,1,w,a) empty(2,2,1)";
+
+    #[test]
+    fn binary_round_trip_matches_text() {
+        let text = parse_sol(SOLUTION).unwrap();
+        let binary = decode(&encode(&text).unwrap()).unwrap();
+        assert_eq!(binary, text);
+    }
+
+    #[test]
+    fn truncated_file_is_refused() {
+        let bytes = encode(&parse_sol(SOLUTION).unwrap()).unwrap();
+        for len in [MAGIC.len() + 1, bytes.len() / 2, bytes.len() - 1] {
+            assert!(matches!(decode(&bytes[..len]), Err(BinaryError::Truncated)));
+        }
+    }
+
+    #[test]
+    fn oversized_count_is_refused() {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(decode(&bytes), Err(BinaryError::Truncated)));
+    }
+
+    #[test]
+    fn invalid_turns_are_refused() {
+        let mut bytes = encode(&parse_sol("block_pos(1,1,1,a,1)").unwrap()).unwrap();
+        // The last turns byte of the only block, before the empty pipe and
+        // empty cell counts.
+        let turns = bytes.len() - 8 - 1;
+        bytes[turns] = 5;
+        assert!(matches!(decode(&bytes), Err(BinaryError::InvalidTurns(5))));
+    }
+}
@@ -0,0 +1,101 @@
+//! Blink comparison between the models of a solver run: S swaps the shown
+//! model for the next one in place, without touching the camera, and
+//! `--blink <seconds>` cycles through them on its own so differences
+//! stand out as flicker. The HUD shows which model is on screen.
+
+use bevy::prelude::*;
+
+use crate::{
+    BlockMaterials, Config, Positions, SolutionEntities,
+    hud::{HudRoot, hud_line, spawn_hud_root},
+    keymap::{Action, Keymap},
+    respawn_solution,
+};
+
+pub struct BlinkPlugin {
+    /// Every model in the order the solver printed them.
+    pub models: Vec<Positions>,
+    /// Seconds between automatic swaps, if blinking.
+    pub interval: Option<f32>,
+    /// Index of the model shown at startup.
+    pub current: usize,
+}
+
+impl Plugin for BlinkPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Solutions {
+            models: self.models.clone(),
+            current: self.current,
+            timer: self
+                .interval
+                .map(|seconds| Timer::from_seconds(seconds, TimerMode::Repeating)),
+        })
+        .add_systems(Startup, spawn_solution_index.after(spawn_hud_root))
+        .add_systems(Update, cycle_solutions_system);
+    }
+}
+
+#[derive(Resource)]
+pub struct Solutions {
+    models: Vec<Positions>,
+    pub current: usize,
+    timer: Option<Timer>,
+}
+
+#[derive(Component)]
+struct SolutionIndexText;
+
+fn index(solutions: &Solutions) -> String {
+    format!("solution {}/{}", solutions.current + 1, solutions.models.len())
+}
+
+fn spawn_solution_index(
+    mut commands: Commands,
+    solutions: Res<Solutions>,
+    root: Option<Single<Entity, With<HudRoot>>>,
+) {
+    if let Some(root) = root {
+        commands
+            .entity(*root)
+            .with_child((hud_line(index(&solutions), true), SolutionIndexText));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cycle_solutions_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    key_input: Option<Res<ButtonInput<KeyCode>>>,
+    keymap: Res<Keymap>,
+    mut solutions: ResMut<Solutions>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    config: Res<Config>,
+    block_materials: Res<BlockMaterials>,
+    spawned: Query<Entity, SolutionEntities>,
+    mut text_query: Query<&mut Text, With<SolutionIndexText>>,
+) {
+    let pressed = key_input.is_some_and(|key_input| keymap.just_pressed(Action::NextSolution, &key_input));
+    let blinked = solutions
+        .timer
+        .as_mut()
+        .is_some_and(|timer| timer.tick(time.delta()).just_finished());
+    if !pressed && !blinked {
+        return;
+    }
+
+    solutions.current = (solutions.current + 1) % solutions.models.len();
+    respawn_solution(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &spawned,
+        solutions.models[solutions.current].clone(),
+        &config,
+        &block_materials,
+    );
+
+    for mut text in text_query.iter_mut() {
+        text.0 = index(&solutions);
+    }
+}
@@ -0,0 +1,116 @@
+//! A bar chart in the HUD of how many cells each block id covers, in the
+//! block's own color, to check the solver placed the expected pieces.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{
+    BlockInfo, BlockMaterials, Positions,
+    hud::{HudRoot, spawn_hud_root, toggle_display},
+    keymap::{Action, Keymap},
+    legend,
+};
+
+/// Width in pixels of the longest bar.
+const CHART_WIDTH: f32 = 160.0;
+const BAR_HEIGHT: f32 = 12.0;
+
+pub struct BlockChartPlugin;
+
+impl Plugin for BlockChartPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_block_chart.after(spawn_hud_root))
+            .add_systems(Update, (toggle_block_chart_system, update_block_chart_system));
+    }
+}
+
+#[derive(Component)]
+struct BlockChart;
+
+fn spawn_block_chart(mut commands: Commands, root: Single<Entity, With<HudRoot>>) {
+    commands.entity(*root).with_child((
+        Node {
+            display: Display::None,
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(2.0),
+            ..default()
+        },
+        BlockChart,
+    ));
+}
+
+fn toggle_block_chart_system(
+    key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut query: Query<&mut Node, With<BlockChart>>,
+) {
+    if keymap.just_pressed(Action::BlockChart, &key_input) {
+        for mut node in query.iter_mut() {
+            toggle_display(&mut node);
+        }
+    }
+}
+
+// Rebuilt whenever the solution is (re)loaded.
+fn update_block_chart_system(
+    mut commands: Commands,
+    positions: Res<Positions>,
+    block_materials: Res<BlockMaterials>,
+    materials: Res<Assets<StandardMaterial>>,
+    chart: Single<Entity, With<BlockChart>>,
+) {
+    if !positions.is_changed() && !block_materials.is_changed() {
+        return;
+    }
+
+    let mut cells: HashMap<&String, usize> = HashMap::new();
+    for BlockInfo { id, .. } in positions.positions.values() {
+        *cells.entry(id).or_default() += 1;
+    }
+    let largest = cells.values().copied().max().unwrap_or(0).max(1);
+
+    let mut chart = commands.entity(*chart);
+    chart.despawn_descendants();
+    chart.with_children(|parent| {
+        for id in legend::sorted_ids(cells.keys().copied()) {
+            let count = cells[id];
+            let color = block_materials
+                .by_id
+                .get(id)
+                .and_then(|handle| materials.get(handle))
+                .map_or(Color::WHITE, |material| material.base_color);
+
+            parent
+                .spawn(Node {
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(6.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn((
+                        Text::new(format!("{id:>3}")),
+                        TextFont {
+                            font_size: 12.0,
+                            ..default()
+                        },
+                    ));
+                    row.spawn((
+                        Node {
+                            width: Val::Px(CHART_WIDTH * count as f32 / largest as f32),
+                            height: Val::Px(BAR_HEIGHT),
+                            ..default()
+                        },
+                        BackgroundColor(color),
+                    ));
+                    row.spawn((
+                        Text::new(count.to_string()),
+                        TextFont {
+                            font_size: 12.0,
+                            ..default()
+                        },
+                    ));
+                });
+        }
+    });
+}
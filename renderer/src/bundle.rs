@@ -0,0 +1,254 @@
+//! Bundles: a JSON file holding the box dimensions together with the
+//! solution, loaded with `--bundle` and written with `--save-bundle`, so a
+//! shared solution can't be shown in a box of the wrong size.
+//! `--export-json <path>` writes the solution alone, without the
+//! dimensions, for other tools to pick up, and `--import-json <path>`
+//! reads it back into the box given on the command line.
+//!
+//! ```json
+//! {
+//!   "width": 3, "height": 3, "depth": 3,
+//!   "blocks": [{ "cell": [1, 1, 1], "id": "1", "unit": 1, "turns": [0, 1, 0] }],
+//!   "pipes": [{ "cell": [1, 1, 1], "from": "west", "to": "above" }],
+//!   "empty": [[2, 1, 1]]
+//! }
+//! ```
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs, io,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BlockInfo, Config, Direction, Orientation, Positions};
+
+type Cell = (usize, usize, usize);
+
+#[derive(Serialize, Deserialize)]
+pub struct Bundle {
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+    #[serde(flatten)]
+    solution: SolutionJson,
+}
+
+/// The blocks, pipes and empty cells of a bundle.
+#[derive(Serialize, Deserialize)]
+pub struct SolutionJson {
+    blocks: Vec<BundleBlock>,
+    pipes: Vec<BundlePipe>,
+    #[serde(default)]
+    empty: Vec<Cell>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BundleBlock {
+    cell: Cell,
+    id: String,
+    /// Missing from bundles saved before units were kept, read as 0.
+    #[serde(default)]
+    unit: u32,
+    /// Quarter turns about x, y and z, none if missing.
+    #[serde(default)]
+    turns: [u8; 3],
+}
+
+#[derive(Serialize, Deserialize)]
+struct BundlePipe {
+    cell: Cell,
+    from: Direction,
+    to: Direction,
+}
+
+impl Bundle {
+    pub fn load(path: &Path) -> Result<Bundle, BundleError> {
+        let contents = fs::read_to_string(path).map_err(BundleError::Io)?;
+        serde_json::from_str(&contents).map_err(BundleError::Json)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), BundleError> {
+        let contents = serde_json::to_string_pretty(self).map_err(BundleError::Json)?;
+        fs::write(path, contents).map_err(BundleError::Io)
+    }
+
+    pub fn new(config: &Config, positions: &Positions) -> Bundle {
+        Bundle {
+            width: config.width,
+            height: config.height,
+            depth: config.depth,
+            solution: SolutionJson::new(positions),
+        }
+    }
+
+    pub fn positions(&self) -> Result<Positions, BundleError> {
+        self.solution.positions()
+    }
+}
+
+impl SolutionJson {
+    pub fn load(path: &Path) -> Result<SolutionJson, BundleError> {
+        let contents = fs::read_to_string(path).map_err(BundleError::Io)?;
+        serde_json::from_str(&contents).map_err(BundleError::Json)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), BundleError> {
+        let contents = serde_json::to_string_pretty(self).map_err(BundleError::Json)?;
+        fs::write(path, contents).map_err(BundleError::Io)
+    }
+
+    /// Cells are sorted so saving the same solution twice gives the same file.
+    pub fn new(positions: &Positions) -> SolutionJson {
+        let mut blocks: Vec<_> = positions
+            .positions
+            .iter()
+            .map(|(&cell, block)| BundleBlock {
+                cell,
+                id: block.id.clone(),
+                unit: block.unit,
+                turns: block.orientation.0,
+            })
+            .collect();
+        blocks.sort_by_key(|block| block.cell);
+
+        let mut pipes: Vec<_> = positions
+            .pipe_positions
+            .iter()
+            .flat_map(|(&cell, segments)| {
+                segments.iter().map(move |&(from, to)| BundlePipe { cell, from, to })
+            })
+            .collect();
+        pipes.sort_by_key(|pipe| (pipe.cell, pipe.from, pipe.to));
+
+        let mut empty: Vec<_> = positions.empty.iter().copied().collect();
+        empty.sort();
+
+        SolutionJson { blocks, pipes, empty }
+    }
+
+    /// Checked like the solver's atoms: two blocks in one cell are refused
+    /// and a segment listed both ways is kept once.
+    pub fn positions(&self) -> Result<Positions, BundleError> {
+        let mut positions: HashMap<Cell, BlockInfo> = HashMap::new();
+        for block in &self.blocks {
+            if let Some(other) = positions.get(&block.cell)
+                && other.id != block.id
+            {
+                return Err(BundleError::Overlap {
+                    cell: block.cell,
+                    id: block.id.clone(),
+                    other: other.id.clone(),
+                });
+            }
+            if let Some(&turns) = block.turns.iter().find(|&&turns| turns > 3) {
+                return Err(BundleError::InvalidTurns {
+                    id: block.id.clone(),
+                    turns,
+                });
+            }
+            let info = BlockInfo {
+                id: block.id.clone(),
+                unit: block.unit,
+                orientation: Orientation(block.turns),
+            };
+            positions.insert(block.cell, info);
+        }
+
+        let mut pipe_positions: HashMap<Cell, Vec<(Direction, Direction)>> = HashMap::new();
+        for pipe in &self.pipes {
+            let segments = pipe_positions.entry(pipe.cell).or_default();
+            if !segments.contains(&(pipe.from, pipe.to)) && !segments.contains(&(pipe.to, pipe.from)) {
+                segments.push((pipe.from, pipe.to));
+            }
+        }
+
+        Ok(Positions {
+            positions,
+            pipe_positions,
+            empty: self.empty.iter().copied().collect::<HashSet<_>>(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum BundleError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// Two different blocks listed in the same cell.
+    Overlap { cell: Cell, id: String, other: String },
+    /// A block turned by more than three quarter turns about an axis.
+    InvalidTurns { id: String, turns: u8 },
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BundleError::Io(err) => write!(f, "{err}"),
+            BundleError::Json(err) => write!(f, "{err}"),
+            BundleError::Overlap {
+                cell: (x, y, z),
+                id,
+                other,
+            } => write!(f, "block {id}: cell ({x},{y},{z}) already holds block {other}"),
+            BundleError::InvalidTurns { id, turns } => {
+                write!(f, "block {id}: {turns} quarter turns, expected at most 3")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BundleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_blocks_are_refused() {
+        let solution: SolutionJson = serde_json::from_str(
+            r#"{
+                "blocks": [{ "cell": [1, 1, 1], "id": "a" }, { "cell": [1, 1, 1], "id": "b" }],
+                "pipes": []
+            }"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            solution.positions(),
+            Err(BundleError::Overlap { cell: (1, 1, 1), ref id, ref other }) if id == "b" && other == "a"
+        ));
+    }
+
+    #[test]
+    fn invalid_turns_are_refused() {
+        let solution: SolutionJson = serde_json::from_str(
+            r#"{
+                "blocks": [{ "cell": [1, 1, 1], "id": "a", "turns": [5, 0, 0] }],
+                "pipes": []
+            }"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            solution.positions(),
+            Err(BundleError::InvalidTurns { ref id, turns: 5 }) if id == "a"
+        ));
+    }
+
+    #[test]
+    fn segments_listed_both_ways_are_kept_once() {
+        let solution: SolutionJson = serde_json::from_str(
+            r#"{
+                "blocks": [{ "cell": [1, 1, 1], "id": "a" }, { "cell": [1, 1, 1], "id": "a" }],
+                "pipes": [
+                    { "cell": [1, 1, 1], "from": "west", "to": "above" },
+                    { "cell": [1, 1, 1], "from": "above", "to": "west" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let positions = solution.positions().unwrap();
+        assert_eq!(positions.positions.len(), 1);
+        assert_eq!(positions.pipe_positions[&(1, 1, 1)], [(Direction::West, Direction::Above)]);
+    }
+}
@@ -0,0 +1,72 @@
+//! Camera framing helpers shared by the interactive and headless cameras.
+
+use bevy::{
+    image::BevyDefault,
+    prelude::*,
+    render::{render_resource::TextureFormat, renderer::RenderAdapter},
+};
+
+use crate::Config;
+
+/// Center of the box in world space. Cells are unit cubes centered on
+/// integer coordinates starting at 1.
+pub fn box_center(config: &Config) -> Vec3 {
+    config.origin
+        + Vec3::new(
+            (config.width as f32 + 1.0) / 2.0,
+            (config.height as f32 + 1.0) / 2.0,
+            (config.depth as f32 + 1.0) / 2.0,
+        )
+}
+
+/// Radius of the sphere enclosing the whole box.
+pub fn bounding_radius(config: &Config) -> f32 {
+    Vec3::new(config.width as f32, config.height as f32, config.depth as f32).length() / 2.0
+}
+
+/// Distance from the box center at which the whole box fits in a
+/// perspective view with the given vertical field of view (radians).
+pub fn framing_distance(config: &Config, fov: f32) -> f32 {
+    bounding_radius(config) / (fov / 2.0).sin()
+}
+
+/// Perspective projection with the configured field of view.
+pub fn projection(config: &Config) -> Projection {
+    Projection::Perspective(PerspectiveProjection {
+        fov: config.fov,
+        ..default()
+    })
+}
+
+/// Camera transform orbiting `focus`, using the same yaw/pitch convention
+/// as `PanOrbitCamera` so headless and interactive views line up.
+pub fn orbit_transform(focus: Vec3, yaw: f32, pitch: f32, radius: f32) -> Transform {
+    let rotation = Quat::from_rotation_y(yaw) * Quat::from_rotation_x(-pitch);
+    Transform {
+        translation: focus + rotation * Vec3::new(0.0, 0.0, radius),
+        rotation,
+        ..default()
+    }
+}
+
+/// Highest sample count at or below `requested` that the adapter supports
+/// for the main view format, warning when it has to fall back.
+pub fn supported_msaa(requested: Msaa, adapter: &RenderAdapter) -> Msaa {
+    let flags = adapter
+        .get_texture_format_features(TextureFormat::bevy_default())
+        .flags;
+    let msaa = [Msaa::Sample8, Msaa::Sample4, Msaa::Sample2]
+        .into_iter()
+        .filter(|msaa| msaa.samples() <= requested.samples())
+        .find(|msaa| flags.sample_count_supported(msaa.samples()))
+        .unwrap_or(Msaa::Off);
+
+    if msaa != requested {
+        warn!(
+            "MSAA x{} is not supported by this GPU, falling back to x{}",
+            requested.samples(),
+            msaa.samples()
+        );
+    }
+    msaa
+}
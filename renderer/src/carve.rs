@@ -0,0 +1,102 @@
+//! Carving: click a block to select it and press Delete to take it out of
+//! the view, uncovering what's behind it; U puts the last one back in its
+//! original color. Only the view changes, never the solution, and a
+//! respawned solution (`--watch`, blink) starts whole again.
+
+use bevy::prelude::*;
+
+use crate::{
+    Block, BlockMaterials, Positions,
+    keymap::{Action, Keymap},
+};
+
+const SELECTED_COLOR: Color = Color::srgb(1.0, 0.85, 0.1);
+
+pub struct CarvePlugin;
+
+impl Plugin for CarvePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Carving>().add_systems(
+            Update,
+            (select_block_system, carve_system, draw_selection_system).chain(),
+        );
+    }
+}
+
+#[derive(Resource, Default)]
+struct Carving {
+    selected: Option<Entity>,
+    /// Carved blocks, most recent last.
+    removed: Vec<CarvedBlock>,
+}
+
+struct CarvedBlock {
+    cell: (usize, usize, usize),
+    mesh: Handle<Mesh>,
+    transform: Transform,
+}
+
+fn select_block_system(
+    mut click_events: EventReader<Pointer<Click>>,
+    blocks: Query<(), With<Block>>,
+    mut carving: ResMut<Carving>,
+) {
+    for event in click_events.read() {
+        if event.button == PointerButton::Primary && blocks.contains(event.target) {
+            carving.selected = Some(event.target);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn carve_system(
+    mut commands: Commands,
+    key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    positions: Res<Positions>,
+    block_materials: Res<BlockMaterials>,
+    mut carving: ResMut<Carving>,
+    blocks: Query<(&Block, &Mesh3d, &Transform)>,
+) {
+    if positions.is_changed() {
+        carving.removed.clear();
+    }
+
+    if keymap.just_pressed(Action::Carve, &key_input)
+        && let Some(entity) = carving.selected.take()
+        && let Ok((block, mesh, transform)) = blocks.get(entity)
+    {
+        carving.removed.push(CarvedBlock {
+            cell: (block.x, block.y, block.z),
+            mesh: mesh.0.clone(),
+            transform: *transform,
+        });
+        commands.entity(entity).despawn();
+    }
+
+    if keymap.just_pressed(Action::UndoCarve, &key_input)
+        && let Some(carved) = carving.removed.pop()
+        && let Some(id) = positions.block_id(&carved.cell)
+    {
+        let (x, y, z) = carved.cell;
+        commands.spawn((
+            Mesh3d(carved.mesh),
+            MeshMaterial3d(block_materials.cell(id, carved.cell).clone()),
+            carved.transform,
+            Block { x, y, z },
+        ));
+    }
+}
+
+fn draw_selection_system(
+    carving: Res<Carving>,
+    blocks: Query<&GlobalTransform, With<Block>>,
+    mut gizmos: Gizmos,
+) {
+    if let Some(transform) = carving.selected.and_then(|entity| blocks.get(entity).ok()) {
+        gizmos.cuboid(
+            Transform::from_translation(transform.translation()).with_scale(Vec3::splat(1.04)),
+            SELECTED_COLOR,
+        );
+    }
+}
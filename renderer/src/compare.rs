@@ -0,0 +1,188 @@
+//! `--compare-dims 3x3x3,4x4x4`: runs the generator once per size and
+//! lines the boxes up along +X, a couple of cells apart, each labeled with
+//! its dimensions, to see how the packing changes with the box.
+//!
+//! The first size is the main solution, with every other option applying
+//! to it as usual. The others are plain copies for looking at: blocks in
+//! their id colors and stub pipes, left out of the toggles and overlays.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_panorbit_camera::PanOrbitCamera;
+
+use crate::{BlockInfo, Config, Positions, id_color, spawn_camera};
+
+/// Empty cells between two boxes.
+const GAP: f32 = 2.0;
+
+type Dimensions = (usize, usize, usize);
+
+pub fn parse_dimensions(s: &str) -> Result<Dimensions, String> {
+    let sizes: Vec<&str> = s.split('x').collect();
+    match sizes[..] {
+        [w, h, d] => match (w.parse(), h.parse(), d.parse()) {
+            (Ok(w), Ok(h), Ok(d)) if w > 0 && h > 0 && d > 0 => Ok((w, h, d)),
+            _ => Err(format!("expected three positive integers; got `{s}`")),
+        },
+        _ => Err(format!("expected WIDTHxHEIGHTxDEPTH; got `{s}`")),
+    }
+}
+
+pub struct ComparePlugin {
+    /// Size of the main box.
+    pub main: Dimensions,
+    /// The other sizes with their solutions, in order.
+    pub others: Vec<(Dimensions, Positions)>,
+}
+
+impl Plugin for ComparePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Comparison {
+            main: self.main,
+            others: self.others.clone(),
+        })
+        .add_systems(Startup, (spawn_comparison, frame_row_system.after(spawn_camera)))
+        .add_systems(Update, place_labels_system);
+    }
+}
+
+#[derive(Resource)]
+struct Comparison {
+    main: Dimensions,
+    others: Vec<(Dimensions, Positions)>,
+}
+
+impl Comparison {
+    /// Corners of the box around the whole row.
+    fn bounds(&self) -> (Vec3, Vec3) {
+        let mut max = Vec3::new(self.main.0 as f32, self.main.1 as f32, self.main.2 as f32);
+        for &((width, height, depth), _) in &self.others {
+            max.x += GAP + width as f32;
+            max.y = max.y.max(height as f32);
+            max.z = max.z.max(depth as f32);
+        }
+        (Vec3::splat(0.5), max + 0.5)
+    }
+}
+
+/// A dimensions label, kept above the top of its box.
+#[derive(Component)]
+struct DimensionsLabel {
+    anchor: Vec3,
+}
+
+/// Center of the top face of a box of `dimensions` whose first column of
+/// cells sits at `x_offset + 1`.
+fn label_anchor((width, height, depth): Dimensions, x_offset: f32) -> Vec3 {
+    Vec3::new(
+        x_offset + (width as f32 + 1.0) / 2.0,
+        height as f32 + 1.0,
+        (depth as f32 + 1.0) / 2.0,
+    )
+}
+
+fn spawn_label(commands: &mut Commands, (width, height, depth): Dimensions, anchor: Vec3) {
+    commands.spawn((
+        Text::new(format!("{width}x{height}x{depth}")),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        DimensionsLabel { anchor },
+    ));
+}
+
+fn spawn_comparison(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    comparison: Res<Comparison>,
+    config: Res<Config>,
+) {
+    let cube = meshes.add(Cuboid::new(1.0, 1.0, 1.0));
+    let pipe_material = materials.add(Color::srgb_u8(255, 0, 0));
+
+    let main_anchor = config.origin + label_anchor(comparison.main, 0.0);
+    spawn_label(&mut commands, comparison.main, main_anchor);
+
+    let mut x_offset = comparison.main.0 as f32 + GAP;
+    for &(dimensions, ref positions) in &comparison.others {
+        let offset = config.origin + Vec3::new(x_offset, 0.0, 0.0);
+        let mut block_materials: HashMap<&String, Handle<StandardMaterial>> = HashMap::new();
+
+        for (&(x, y, z), BlockInfo { id, .. }) in &positions.positions {
+            let material = block_materials
+                .entry(id)
+                .or_insert_with(|| materials.add(id_color(id, &config)))
+                .clone();
+            commands.spawn((
+                Mesh3d(cube.clone()),
+                MeshMaterial3d(material),
+                Transform::from_translation(offset + Vec3::new(x as f32, y as f32, z as f32)),
+            ));
+        }
+
+        for (&(x, y, z), segments) in &positions.pipe_positions {
+            for &(in_dir, out_dir) in segments {
+                for dir in [in_dir, out_dir] {
+                    let size = Vec3::new(dir.pipe_width(), dir.pipe_height(), dir.pipe_depth());
+                    let mut transform = dir.pipe_transform(x as f32, y as f32, z as f32);
+                    transform.translation += offset;
+                    commands.spawn((
+                        Mesh3d(meshes.add(Cuboid::from_size(size))),
+                        MeshMaterial3d(pipe_material.clone()),
+                        transform,
+                    ));
+                }
+            }
+        }
+
+        let anchor = config.origin + label_anchor(dimensions, x_offset);
+        spawn_label(&mut commands, dimensions, anchor);
+        x_offset += dimensions.0 as f32 + GAP;
+    }
+}
+
+fn place_labels_system(
+    camera_query: Option<Single<(&Camera, &GlobalTransform), With<PanOrbitCamera>>>,
+    mut labels: Query<(&DimensionsLabel, &mut Node, &ComputedNode)>,
+) {
+    let Some(camera_query) = camera_query else {
+        return;
+    };
+    let (camera, camera_transform) = *camera_query;
+
+    for (label, mut node, computed) in labels.iter_mut() {
+        match camera.world_to_viewport(camera_transform, label.anchor) {
+            Ok(position) => {
+                // Centered over the anchor, just above it.
+                let size = computed.size() * computed.inverse_scale_factor();
+                node.left = Val::Px(position.x - size.x / 2.0);
+                node.top = Val::Px(position.y - size.y);
+                node.display = Display::Flex;
+            }
+            Err(_) => node.display = Display::None,
+        }
+    }
+}
+
+/// Aims the orbit camera at the middle of the row and backs it off until
+/// every box fits.
+fn frame_row_system(
+    comparison: Res<Comparison>,
+    config: Res<Config>,
+    mut pan_orbit_query: Query<&mut PanOrbitCamera>,
+) {
+    let (min, max) = comparison.bounds();
+    let center = config.origin + (min + max) / 2.0;
+    let distance = (max - min).length() / 2.0 / (config.fov / 2.0).sin();
+    for mut pan_orbit in pan_orbit_query.iter_mut() {
+        pan_orbit.target_focus = center;
+        pan_orbit.target_radius = distance;
+    }
+}
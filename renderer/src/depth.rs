@@ -0,0 +1,150 @@
+//! `--export-depth <path>`: saves a grayscale depth map of the scene, for
+//! depth of field or relighting in other tools.
+//!
+//! Rather than reading the depth buffer back, every mesh is drawn unlit
+//! and black under linear fog that turns fully white at the far side of
+//! the box, so the GPU writes the normalized distance straight into the
+//! image: black at the nearest point of the box's bounding sphere, white at
+//! its farthest and for the background. The capture comes back
+//! sRGB-encoded and is decoded on saving, so gray levels are linear in
+//! distance. The view is the first turntable frame.
+
+use std::{f32::consts::FRAC_PI_4, path::PathBuf};
+
+use bevy::{
+    core_pipeline::tonemapping::{DebandDither, Tonemapping},
+    pbr::{DistanceFog, FogFalloff},
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        view::screenshot::ScreenshotCaptured,
+    },
+};
+
+use crate::{
+    Config, camera,
+    offscreen::{self, FrameSequence, FrameStep, PipelinesReady},
+};
+
+const DEPTH_WIDTH: u32 = 1280;
+const DEPTH_HEIGHT: u32 = 720;
+
+pub struct DepthPlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for DepthPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DepthExport { path: self.path.clone() })
+        .add_systems(Startup, spawn_depth_camera)
+        // After anything that recolors blocks during Update.
+        .add_systems(PostUpdate, use_depth_material_system)
+        .add_systems(Update, capture_depth);
+    }
+}
+
+#[derive(Resource)]
+struct DepthExport {
+    path: PathBuf,
+}
+
+#[derive(Component)]
+struct DepthCamera {
+    target: Handle<Image>,
+}
+
+#[derive(Resource)]
+struct DepthMaterial(Handle<StandardMaterial>);
+
+fn spawn_depth_camera(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    config: Res<Config>,
+) {
+    let target = offscreen::render_target(&mut images, DEPTH_WIDTH, DEPTH_HEIGHT);
+    let distance = camera::framing_distance(&config, config.fov);
+    let radius = camera::bounding_radius(&config);
+
+    commands.spawn((
+        Camera3d::default(),
+        Camera {
+            target: RenderTarget::Image(target.clone()),
+            clear_color: ClearColorConfig::Custom(Color::WHITE),
+            ..default()
+        },
+        camera::orbit_transform(camera::box_center(&config), FRAC_PI_4, FRAC_PI_4, distance),
+        camera::projection(&config),
+        DistanceFog {
+            color: Color::WHITE,
+            falloff: FogFalloff::Linear {
+                start: distance - radius,
+                end: distance + radius,
+            },
+            ..default()
+        },
+        // Anything between the fog and the image would bend the gray levels.
+        Tonemapping::None,
+        DebandDither::Disabled,
+        Msaa::Off,
+        DepthCamera { target },
+        FrameSequence::new(1),
+    ));
+
+    commands.insert_resource(DepthMaterial(materials.add(StandardMaterial {
+        base_color: Color::BLACK,
+        unlit: true,
+        ..default()
+    })));
+}
+
+fn use_depth_material_system(
+    depth_material: Res<DepthMaterial>,
+    mut meshes: Query<&mut MeshMaterial3d<StandardMaterial>>,
+) {
+    for mut material in meshes.iter_mut() {
+        if material.0 != depth_material.0 {
+            material.0 = depth_material.0.clone();
+        }
+    }
+}
+
+/// Decodes an sRGB-encoded gray level back to the linear value the fog
+/// wrote.
+fn linear_gray(encoded: u8) -> u8 {
+    (Srgba::gamma_function(encoded as f32 / 255.0) * 255.0).round() as u8
+}
+
+fn capture_depth(
+    mut commands: Commands,
+    ready: Res<PipelinesReady>,
+    camera: Single<(Entity, &DepthCamera, &mut FrameSequence)>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let (entity, depth_camera, mut sequence) = camera.into_inner();
+    match sequence.step(&ready) {
+        FrameStep::Wait | FrameStep::Pose(_) => (),
+        FrameStep::Capture(_) => {
+            offscreen::capture(&mut commands, entity, &depth_camera.target).observe(
+                |trigger: Trigger<ScreenshotCaptured>, export: Res<DepthExport>| {
+                    match trigger.event().0.clone().try_into_dynamic() {
+                        Ok(image) => {
+                            let mut gray = image.to_luma8();
+                            for pixel in gray.pixels_mut() {
+                                pixel.0[0] = linear_gray(pixel.0[0]);
+                            }
+                            match gray.save(&export.path) {
+                                Ok(()) => info!("Saved depth map to {}", export.path.display()),
+                                Err(err) => error!("Failed to save {}: {err}", export.path.display()),
+                            }
+                        }
+                        Err(err) => error!("Failed to convert the depth map: {err}"),
+                    }
+                },
+            );
+        }
+        FrameStep::Done => {
+            exit.send(AppExit::Success);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Assembly animation: blocks fall from above the box into their solved
+//! cells, one layer after another, and bounce to rest. Each cell's pipes
+//! stay hidden until its block has landed, or for good while the pipes
+//! are toggled off.
+
+use bevy::{math::curve::easing::EasingCurve, prelude::*};
+
+use crate::{
+    Block, Config, Pipe,
+    keymap::{Action, Keymap},
+};
+
+/// Delay between one layer starting to fall and the next.
+const LAYER_DELAY: f32 = 0.25;
+
+pub struct DropPlugin {
+    /// Average fall speed, in cells per second.
+    pub speed: f32,
+}
+
+impl Plugin for DropPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DropAnimation {
+            speed: self.speed,
+            started: None,
+        })
+        .add_systems(Update, (start_drop_system, animate_drop_system).chain());
+    }
+}
+
+#[derive(Resource)]
+struct DropAnimation {
+    speed: f32,
+    /// Elapsed time at which the running animation started.
+    started: Option<f32>,
+}
+
+fn start_drop_system(
+    key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    time: Res<Time>,
+    mut drop: ResMut<DropAnimation>,
+) {
+    if keymap.just_pressed(Action::DropBlocks, &key_input) {
+        drop.started = Some(time.elapsed_secs());
+    }
+}
+
+fn animate_drop_system(
+    time: Res<Time>,
+    config: Res<Config>,
+    mut drop: ResMut<DropAnimation>,
+    mut blocks: Query<(&Block, &mut Transform)>,
+    mut pipes: Query<(&Pipe, &mut Visibility)>,
+) {
+    let Some(started) = drop.started else {
+        return;
+    };
+
+    // Every block starts one cell above the top of the box.
+    let fall_height = config.height as f32 + 1.0;
+    let fall_duration = fall_height / drop.speed;
+    let elapsed = time.elapsed_secs() - started;
+    let landed_at = |y: usize| layer_delay(y) + fall_duration;
+
+    let fall = EasingCurve::new(fall_height, 0.0, EaseFunction::BounceOut);
+    for (block, mut transform) in blocks.iter_mut() {
+        let t = (elapsed - layer_delay(block.y)) / fall_duration;
+        transform.translation.y = config.origin.y + block.y as f32 + fall.sample_clamped(t);
+    }
+
+    for (pipe, mut visibility) in pipes.iter_mut() {
+        *visibility = if elapsed >= landed_at(pipe.y) && !config.hide_pipes {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    if elapsed >= landed_at(config.height) {
+        drop.started = None;
+    }
+}
+
+/// Time after the start at which layer `y` begins to fall.
+fn layer_delay(y: usize) -> f32 {
+    (y - 1) as f32 * LAYER_DELAY
+}
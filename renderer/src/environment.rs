@@ -0,0 +1,181 @@
+//! `--environment`: surrounds the box with a vertical gradient or an
+//! image skybox instead of the flat clear color, on every 3D camera.
+//!
+//! The gradient runs from a lighter shade of the clear color overhead to a
+//! darker one underfoot unless `--gradient-top`/`--gradient-bottom` say
+//! otherwise. A skybox image is either a cubemap (KTX2, DDS) or six square
+//! faces stacked vertically in the order +X, -X, +Y, -Y, +Z, -Z.
+
+use std::path::PathBuf;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    core_pipeline::Skybox,
+    prelude::*,
+    render::render_resource::{
+        Extent3d, TextureDimension, TextureFormat, TextureViewDescriptor, TextureViewDimension,
+    },
+};
+
+/// Side in pixels of each face of the generated gradient cubemap.
+const GRADIENT_FACE_SIZE: u32 = 64;
+
+/// Skybox brightness that shows the image at its own colors under the
+/// default camera exposure.
+const SKYBOX_BRIGHTNESS: f32 = 1000.0;
+
+#[derive(Debug, Clone)]
+pub enum Environment {
+    Gradient,
+    Skybox(PathBuf),
+}
+
+pub fn parse_environment(s: &str) -> Result<Environment, String> {
+    match s.split_once(':') {
+        None if s == "gradient" => Ok(Environment::Gradient),
+        Some(("skybox", path)) if !path.is_empty() => Ok(Environment::Skybox(PathBuf::from(path))),
+        _ => Err(format!("expected `gradient` or `skybox:PATH`; got `{s}`")),
+    }
+}
+
+pub struct EnvironmentPlugin {
+    pub environment: Environment,
+    pub top: Option<Color>,
+    pub bottom: Option<Color>,
+}
+
+impl Plugin for EnvironmentPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EnvironmentSettings {
+            environment: self.environment.clone(),
+            top: self.top,
+            bottom: self.bottom,
+        })
+        .add_systems(Startup, load_environment)
+        .add_systems(Update, (attach_skybox_system, reinterpret_stacked_skybox_system));
+    }
+}
+
+#[derive(Resource)]
+struct EnvironmentSettings {
+    environment: Environment,
+    top: Option<Color>,
+    bottom: Option<Color>,
+}
+
+#[derive(Resource)]
+struct EnvironmentImage {
+    image: Handle<Image>,
+    /// Still to be turned from stacked faces into a cubemap once loaded.
+    stacked: bool,
+}
+
+fn load_environment(
+    mut commands: Commands,
+    settings: Res<EnvironmentSettings>,
+    clear_color: Res<ClearColor>,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let environment = match &settings.environment {
+        Environment::Gradient => {
+            let top = settings.top.unwrap_or(clear_color.0.lighter(0.15));
+            let bottom = settings.bottom.unwrap_or(clear_color.0.darker(0.1));
+            EnvironmentImage {
+                image: images.add(gradient_cubemap(top, bottom)),
+                stacked: false,
+            }
+        }
+        Environment::Skybox(path) => {
+            // Paths are taken relative to the working directory, not the
+            // asset folder.
+            let path = std::path::absolute(path).unwrap_or_else(|_| path.clone());
+            EnvironmentImage {
+                image: asset_server.load(path),
+                stacked: true,
+            }
+        }
+    };
+    commands.insert_resource(environment);
+}
+
+fn gradient_cubemap(top: Color, bottom: Color) -> Image {
+    let size = GRADIENT_FACE_SIZE as usize;
+    let mut data = Vec::with_capacity(size * size * 6 * 4);
+    for face in 0..6 {
+        for row in 0..size {
+            // Height of the view direction through this pixel: the top and
+            // bottom faces are flat, the side faces run from +1 to -1 down
+            // their rows.
+            let y = match face {
+                2 => 1.0,
+                3 => -1.0,
+                _ => 1.0 - 2.0 * (row as f32 + 0.5) / size as f32,
+            };
+            let color = bottom.mix(&top, (y + 1.0) / 2.0).to_srgba().to_u8_array();
+            for _ in 0..size {
+                data.extend_from_slice(&color);
+            }
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: GRADIENT_FACE_SIZE,
+            height: GRADIENT_FACE_SIZE,
+            depth_or_array_layers: 6,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.texture_view_descriptor = Some(cube_view());
+    image
+}
+
+fn cube_view() -> TextureViewDescriptor<'static> {
+    TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::Cube),
+        ..default()
+    }
+}
+
+fn attach_skybox_system(
+    mut commands: Commands,
+    environment: Res<EnvironmentImage>,
+    cameras: Query<Entity, Added<Camera3d>>,
+) {
+    for camera in cameras.iter() {
+        commands.entity(camera).insert(Skybox {
+            image: environment.image.clone(),
+            brightness: SKYBOX_BRIGHTNESS,
+            ..default()
+        });
+    }
+}
+
+fn reinterpret_stacked_skybox_system(
+    mut environment: ResMut<EnvironmentImage>,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if !environment.stacked || !asset_server.is_loaded_with_dependencies(&environment.image) {
+        return;
+    }
+    environment.stacked = false;
+
+    let Some(image) = images.get_mut(&environment.image) else {
+        return;
+    };
+    // Cubemap formats already come with six layers.
+    if image.texture_descriptor.size.depth_or_array_layers == 1 {
+        let layers = image.height() / image.width();
+        if layers != 6 {
+            error!("Skybox image must be six square faces stacked vertically");
+            return;
+        }
+        image.reinterpret_stacked_2d_as_array(layers);
+    }
+    image.texture_view_descriptor = Some(cube_view());
+}
@@ -0,0 +1,154 @@
+//! `--export-faces <dir>`: an elevation drawing set of the box, one
+//! orthographic PNG per face (`face_top.png`, `face_north.png`, ...), each
+//! labeled with the side it shows.
+//!
+//! Every view spans the largest dimension of the box, so all six share the
+//! same scale and can be laid out next to each other. The sides are seen
+//! upright and the top and bottom with north up.
+
+use std::path::PathBuf;
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{RenderTarget, ScalingMode},
+        renderer::RenderAdapter,
+        view::screenshot::save_to_disk,
+    },
+};
+
+use crate::{
+    Config, Direction, camera,
+    offscreen::{self, FrameSequence, FrameStep, PipelinesReady},
+};
+
+const FACE_SIZE: u32 = 768;
+
+/// Half a cell of margin around the box on every side.
+const MARGIN: f32 = 1.0;
+
+pub struct FacesPlugin {
+    pub dir: PathBuf,
+}
+
+impl Plugin for FacesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Faces { dir: self.dir.clone() })
+            .add_systems(Startup, spawn_faces_camera)
+            .add_systems(Update, capture_faces);
+    }
+}
+
+#[derive(Resource)]
+struct Faces {
+    dir: PathBuf,
+}
+
+/// Face shown in frame `frame`, written in the order of `Direction::all`.
+fn face(frame: u32) -> Direction {
+    Direction::all().nth(frame as usize).unwrap()
+}
+
+#[derive(Component)]
+struct FacesCamera {
+    target: Handle<Image>,
+}
+
+#[derive(Component)]
+struct FaceLabel;
+
+fn face_name(face: Direction) -> &'static str {
+    match face {
+        Direction::Above => "top",
+        Direction::Below => "bottom",
+        Direction::North => "north",
+        Direction::South => "south",
+        Direction::East => "east",
+        Direction::West => "west",
+    }
+}
+
+/// Camera outside the box on the `face` side, looking straight at it.
+fn face_transform(config: &Config, face: Direction) -> Transform {
+    let center = camera::box_center(config);
+    let distance = camera::bounding_radius(config) + MARGIN;
+    let up = match face {
+        Direction::Above | Direction::Below => Vec3::Z,
+        _ => Vec3::Y,
+    };
+    Transform::from_translation(center + face.offset() * distance).looking_at(center, up)
+}
+
+fn spawn_faces_camera(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    config: Res<Config>,
+    adapter: Res<RenderAdapter>,
+) {
+    let target = offscreen::render_target(&mut images, FACE_SIZE, FACE_SIZE);
+    let extent = config.width.max(config.height).max(config.depth) as f32 + MARGIN;
+
+    let camera = commands
+        .spawn((
+            Camera3d::default(),
+            Camera {
+                target: RenderTarget::Image(target.clone()),
+                ..default()
+            },
+            face_transform(&config, face(0)),
+            Projection::Orthographic(OrthographicProjection {
+                scaling_mode: ScalingMode::Fixed {
+                    width: extent,
+                    height: extent,
+                },
+                ..OrthographicProjection::default_3d()
+            }),
+            camera::supported_msaa(config.msaa, &adapter),
+            FacesCamera { target },
+            FrameSequence::new(Direction::all().count() as u32),
+        ))
+        .id();
+
+    commands.spawn((
+        Text::new(face_name(face(0))),
+        TextFont {
+            font_size: 24.0,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            left: Val::Px(12.0),
+            ..default()
+        },
+        TargetCamera(camera),
+        FaceLabel,
+    ));
+}
+
+fn capture_faces(
+    mut commands: Commands,
+    faces: Res<Faces>,
+    ready: Res<PipelinesReady>,
+    config: Res<Config>,
+    camera: Single<(Entity, &FacesCamera, &mut FrameSequence, &mut Transform)>,
+    mut label: Single<&mut Text, With<FaceLabel>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let (entity, faces_camera, mut sequence, mut transform) = camera.into_inner();
+    match sequence.step(&ready) {
+        FrameStep::Wait => (),
+        FrameStep::Pose(frame) => {
+            *transform = face_transform(&config, face(frame));
+            label.0 = face_name(face(frame)).to_string();
+        }
+        FrameStep::Capture(frame) => {
+            let path = faces.dir.join(format!("face_{}.png", face_name(face(frame))));
+            offscreen::capture(&mut commands, entity, &faces_camera.target).observe(save_to_disk(path));
+        }
+        FrameStep::Done => {
+            info!("Faces: wrote {} views to {}", sequence.frames(), faces.dir.display());
+            exit.send(AppExit::Success);
+        }
+    }
+}
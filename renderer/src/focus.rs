@@ -0,0 +1,97 @@
+//! Focus mode: a spotlight for studying one region of a large box. With
+//! focus mode on (O), clicking a block keeps every block within the focus
+//! radius of it (in grid steps) as it is and fades the rest to a ghost of
+//! its color. `]` and `[` grow and shrink the radius, which starts at
+//! `--focus-radius`; leaving focus mode restores every block.
+
+use bevy::prelude::*;
+
+use crate::{
+    Block,
+    keymap::{Action, Keymap},
+    overlays::BlockMaterialSet,
+};
+
+/// Opacity of the blocks outside the focus.
+const DIMMED_ALPHA: f32 = 0.08;
+
+type Cell = (usize, usize, usize);
+
+pub struct FocusPlugin {
+    pub radius: usize,
+}
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Focus {
+            enabled: false,
+            center: None,
+            radius: self.radius,
+        })
+        .add_systems(
+            Update,
+            (focus_keys_system, pick_focus_system)
+                .chain()
+                .before(BlockMaterialSet::Overlays),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct Focus {
+    pub enabled: bool,
+    /// The clicked cell, kept when focus mode is left and entered again.
+    pub center: Option<Cell>,
+    pub radius: usize,
+}
+
+impl Focus {
+    /// The block in `cell` is faded.
+    pub fn dims(&self, cell: Cell) -> bool {
+        self.enabled && self.center.is_some_and(|center| grid_distance(center, cell) > self.radius)
+    }
+}
+
+/// Fades the material of a block outside the focus.
+pub fn dim(material: &mut StandardMaterial) {
+    material.base_color.set_alpha(DIMMED_ALPHA);
+    material.alpha_mode = AlphaMode::Blend;
+}
+
+/// Steps between two cells moving one face at a time.
+fn grid_distance((ax, ay, az): Cell, (bx, by, bz): Cell) -> usize {
+    ax.abs_diff(bx) + ay.abs_diff(by) + az.abs_diff(bz)
+}
+
+fn focus_keys_system(key_input: Res<ButtonInput<KeyCode>>, keymap: Res<Keymap>, mut focus: ResMut<Focus>) {
+    if keymap.just_pressed(Action::FocusMode, &key_input) {
+        focus.enabled = !focus.enabled;
+        if focus.enabled && focus.center.is_none() {
+            info!("Focus mode: click a block to focus on it");
+        }
+    }
+    if !focus.enabled {
+        return;
+    }
+    if keymap.just_pressed(Action::FocusGrow, &key_input) {
+        focus.radius += 1;
+    }
+    if keymap.just_pressed(Action::FocusShrink, &key_input) && focus.radius > 0 {
+        focus.radius -= 1;
+    }
+}
+
+fn pick_focus_system(
+    mut click_events: EventReader<Pointer<Click>>,
+    blocks: Query<&Block>,
+    mut focus: ResMut<Focus>,
+) {
+    for event in click_events.read() {
+        if focus.enabled
+            && event.button == PointerButton::Primary
+            && let Ok(block) = blocks.get(event.target)
+        {
+            focus.center = Some((block.x, block.y, block.z));
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Footprint: every column of the box casts a flat translucent square
+//! just above the floor, each filled cell in it adding another layer of
+//! `--footprint-opacity`, so taller columns read darker. A top-down
+//! summary of how the packing is spread vertically. Toggled with J, or on
+//! from the start with `--footprint`.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{
+    Config, Positions,
+    keymap::{Action, Keymap},
+};
+
+/// Just above the floor plane, which sits at 0.49.
+const FOOTPRINT_Y: f32 = 0.495;
+
+pub struct FootprintPlugin {
+    /// Opacity added by each filled cell of a column.
+    pub opacity: f32,
+    pub shown: bool,
+}
+
+impl Plugin for FootprintPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Footprint {
+            opacity: self.opacity,
+            shown: self.shown,
+        })
+        .add_systems(Update, (toggle_footprint_system, spawn_footprint_system).chain());
+    }
+}
+
+#[derive(Resource)]
+pub struct Footprint {
+    opacity: f32,
+    pub shown: bool,
+}
+
+#[derive(Component)]
+struct FootprintQuad;
+
+fn toggle_footprint_system(
+    key_input: Option<Res<ButtonInput<KeyCode>>>,
+    keymap: Res<Keymap>,
+    mut footprint: ResMut<Footprint>,
+    mut quads: Query<&mut Visibility, With<FootprintQuad>>,
+) {
+    if !key_input.is_some_and(|key_input| keymap.just_pressed(Action::Footprint, &key_input)) {
+        return;
+    }
+
+    footprint.shown = !footprint.shown;
+    for mut visibility in quads.iter_mut() {
+        *visibility = if footprint.shown {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Filled cells per `(x, z)` column.
+fn column_heights(positions: &Positions) -> HashMap<(usize, usize), usize> {
+    let mut heights = HashMap::new();
+    for &(x, _, z) in positions.positions.keys() {
+        *heights.entry((x, z)).or_insert(0) += 1;
+    }
+    heights
+}
+
+fn spawn_footprint_system(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    positions: Res<Positions>,
+    config: Res<Config>,
+    footprint: Res<Footprint>,
+    spawned: Query<Entity, With<FootprintQuad>>,
+) {
+    if !positions.is_changed() {
+        return;
+    }
+
+    for entity in spawned.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let quad = meshes.add(Plane3d::default().mesh().size(1.0, 1.0));
+    // One material per column height; stacking `height` layers of the same
+    // opacity leaves `(1 - opacity)^height` of the floor showing.
+    let mut height_materials: HashMap<usize, Handle<StandardMaterial>> = HashMap::new();
+    let visibility = if footprint.shown {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+
+    for ((x, z), height) in column_heights(&positions) {
+        let material = height_materials.entry(height).or_insert_with(|| {
+            let alpha = 1.0 - (1.0 - footprint.opacity).powi(height as i32);
+            materials.add(StandardMaterial {
+                base_color: Color::BLACK.with_alpha(alpha),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            })
+        });
+        let position = config.origin + Vec3::new(x as f32, FOOTPRINT_Y, z as f32);
+        commands.spawn((
+            Mesh3d(quad.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(position),
+            visibility,
+            FootprintQuad,
+        ));
+    }
+}
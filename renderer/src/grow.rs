@@ -0,0 +1,186 @@
+//! `--grow`: resizes the box one cell at a time and re-runs the generator
+//! on the new size, to watch how adding or removing a layer reshapes the
+//! whole packing. Tab picks the dimension, Page Up grows it and Page Down
+//! shrinks it. A size the generator can't fill keeps the box on screen
+//! and says so on the HUD.
+//!
+//! The generator runs on the main thread, so the window stalls until it
+//! is done. The floor and the other pieces sized at startup stay with the
+//! original box.
+
+use bevy::prelude::*;
+
+use crate::{
+    ActiveLayer, BlockMaterials, Config, SolutionEntities,
+    hud::{HudRoot, hud_line, spawn_hud_root},
+    keymap::{Action, Keymap},
+    parse_metadata, parse_sol, respawn_solution, run_generator, split_models, validate_bounds,
+};
+
+pub struct GrowPlugin;
+
+impl Plugin for GrowPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Grow {
+            dimension: Dimension::Width,
+            message: None,
+        })
+        .add_systems(Startup, spawn_grow_status.after(spawn_hud_root))
+        .add_systems(Update, grow_box_system);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    Width,
+    Height,
+    Depth,
+}
+
+impl Dimension {
+    fn next(self) -> Dimension {
+        match self {
+            Dimension::Width => Dimension::Height,
+            Dimension::Height => Dimension::Depth,
+            Dimension::Depth => Dimension::Width,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Dimension::Width => "width",
+            Dimension::Height => "height",
+            Dimension::Depth => "depth",
+        }
+    }
+
+    fn size(self, config: &mut Config) -> &mut usize {
+        match self {
+            Dimension::Width => &mut config.width,
+            Dimension::Height => &mut config.height,
+            Dimension::Depth => &mut config.depth,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct Grow {
+    dimension: Dimension,
+    /// Why the last resize was undone.
+    message: Option<String>,
+}
+
+#[derive(Component)]
+struct GrowStatusText;
+
+fn dimensions(config: &Config) -> String {
+    format!("{}x{}x{}", config.width, config.height, config.depth)
+}
+
+fn status(grow: &Grow, config: &Config) -> String {
+    let status = format!("box {}, resizing its {}", dimensions(config), grow.dimension.name());
+    match &grow.message {
+        Some(message) => format!("{status}\n{message}"),
+        None => status,
+    }
+}
+
+fn spawn_grow_status(
+    mut commands: Commands,
+    grow: Res<Grow>,
+    config: Res<Config>,
+    root: Option<Single<Entity, With<HudRoot>>>,
+) {
+    if let Some(root) = root {
+        commands
+            .entity(*root)
+            .with_child((hud_line(status(&grow, &config), true), GrowStatusText));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn grow_box_system(
+    mut commands: Commands,
+    key_input: Option<Res<ButtonInput<KeyCode>>>,
+    keymap: Res<Keymap>,
+    mut grow: ResMut<Grow>,
+    mut config: ResMut<Config>,
+    mut active_layer: ResMut<ActiveLayer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    block_materials: Res<BlockMaterials>,
+    spawned: Query<Entity, SolutionEntities>,
+    mut text_query: Query<&mut Text, With<GrowStatusText>>,
+) {
+    let Some(key_input) = key_input else {
+        return;
+    };
+
+    let step: isize = if keymap.just_pressed(Action::GrowBox, &key_input) {
+        1
+    } else if keymap.just_pressed(Action::ShrinkBox, &key_input) {
+        -1
+    } else {
+        0
+    };
+    let next = keymap.just_pressed(Action::GrowDimension, &key_input);
+    if step == 0 && !next {
+        return;
+    }
+
+    if next {
+        grow.dimension = grow.dimension.next();
+        grow.message = None;
+    }
+
+    if step != 0 {
+        let dimension = grow.dimension;
+        let previous = *dimension.size(&mut config);
+        grow.message = match previous.checked_add_signed(step).filter(|&size| size > 0) {
+            Some(size) => {
+                *dimension.size(&mut config) = size;
+                let result = run_generator((config.width, config.height, config.depth))
+                    .map_err(|err| format!("generator failed to run: {err}"))
+                    .and_then(|solution| {
+                        let last = *split_models(&solution).last().unwrap();
+                        // No answer set means no filled cells at all.
+                        let positions = match parse_sol(last) {
+                            Ok(positions) if positions.positions.is_empty() => Err("has no solution".to_string()),
+                            Ok(positions) => validate_bounds(&positions, (config.width, config.height, config.depth))
+                                .map(|()| positions)
+                                .map_err(|err| format!("solution doesn't fit the box: {err}")),
+                            Err(err) => Err(format!("solution failed to parse: {err}")),
+                        }?;
+                        Ok((positions, parse_metadata(&solution)))
+                    });
+                match result {
+                    Ok((positions, metadata)) => {
+                        active_layer.y = active_layer.y.min(config.height);
+                        commands.insert_resource(metadata);
+                        respawn_solution(
+                            &mut commands,
+                            &mut meshes,
+                            &mut materials,
+                            &spawned,
+                            positions,
+                            &config,
+                            &block_materials,
+                        );
+                        None
+                    }
+                    Err(err) => {
+                        let message = format!("{} {err}", dimensions(&config));
+                        warn!("{message}");
+                        *dimension.size(&mut config) = previous;
+                        Some(format!("{message}, kept {}", dimensions(&config)))
+                    }
+                }
+            }
+            None => Some(format!("the {} can't shrink below 1", dimension.name())),
+        };
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.0 = status(&grow, &config);
+    }
+}
@@ -0,0 +1,73 @@
+//! Hovering a block outlines every cell sharing its id, showing how one
+//! logical piece is spread through the box. Clicking a pipe piece logs
+//! the cell it runs through and which side of its segment it is on.
+
+use bevy::prelude::*;
+
+use crate::{Block, Pipe, Positions};
+
+const HOVER_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.8);
+
+pub struct HoverPlugin;
+
+impl Plugin for HoverPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HoveredBlock>()
+            .add_systems(Update, ((track_hover_system, draw_hover_system).chain(), log_pipe_click_system));
+    }
+}
+
+/// Id of the block under the cursor, if any.
+#[derive(Resource, Default)]
+struct HoveredBlock(Option<String>);
+
+fn track_hover_system(
+    mut over_events: EventReader<Pointer<Over>>,
+    mut out_events: EventReader<Pointer<Out>>,
+    blocks: Query<&Block>,
+    positions: Res<Positions>,
+    mut hovered: ResMut<HoveredBlock>,
+) {
+    for event in out_events.read() {
+        if blocks.contains(event.target) {
+            hovered.0 = None;
+        }
+    }
+
+    for event in over_events.read() {
+        if let Ok(block) = blocks.get(event.target) {
+            hovered.0 = positions.block_id(&(block.x, block.y, block.z)).cloned();
+        }
+    }
+}
+
+// Outlines rather than a material swap, so the hover stays distinct from
+// (and never fights with) the overlays that recolor blocks.
+fn draw_hover_system(
+    mut gizmos: Gizmos,
+    hovered: Res<HoveredBlock>,
+    positions: Res<Positions>,
+    blocks: Query<(&Block, &GlobalTransform, &ViewVisibility)>,
+) {
+    let Some(id) = &hovered.0 else {
+        return;
+    };
+
+    for (block, transform, visibility) in blocks.iter() {
+        if visibility.get() && positions.block_id(&(block.x, block.y, block.z)) == Some(id) {
+            gizmos.cuboid(
+                Transform::from_translation(transform.translation()).with_scale(Vec3::splat(1.02)),
+                HOVER_COLOR,
+            );
+        }
+    }
+}
+
+fn log_pipe_click_system(mut click_events: EventReader<Pointer<Click>>, pipes: Query<&Pipe>) {
+    for event in click_events.read() {
+        if let Ok(pipe) = pipes.get(event.target) {
+            let side = if pipe.is_inlet { "inlet" } else { "outlet" };
+            info!("Pipe {side} piece at ({},{},{})", pipe.x, pipe.y, pipe.z);
+        }
+    }
+}
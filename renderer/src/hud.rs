@@ -0,0 +1,200 @@
+//! On-screen diagnostic overlays.
+//!
+//! Every overlay is one text line stacked in a column in the top-left
+//! corner. Lines are shown and hidden through `Node::display` so hidden
+//! ones don't leave gaps.
+
+use bevy::prelude::*;
+
+use crate::{
+    Block, Config, Pipe, Positions, SolutionMetadata,
+    keymap::{Action, Keymap},
+    stats,
+};
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Startup,
+            (
+                spawn_hud_root,
+                (
+                    spawn_solution_metadata,
+                    spawn_entity_counts,
+                    spawn_shape_stats,
+                    spawn_help_controls,
+                )
+                    .after(spawn_hud_root),
+            ),
+        )
+            .add_systems(
+                Update,
+                (
+                    toggle_entity_counts_system,
+                    update_entity_counts_system,
+                    update_shape_stats_system,
+                    toggle_help_controls_system,
+                ),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct HudRoot;
+
+pub fn spawn_hud_root(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            left: Val::Px(8.0),
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        HudRoot,
+    ));
+}
+
+/// Bundle for one HUD line, spawned as a child of `HudRoot` by the systems
+/// that own it.
+pub fn hud_line(text: impl Into<String>, shown: bool) -> impl Bundle {
+    (
+        Text::new(text),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        Node {
+            display: if shown { Display::Flex } else { Display::None },
+            ..default()
+        },
+    )
+}
+
+pub fn toggle_display(node: &mut Node) {
+    node.display = match node.display {
+        Display::None => Display::Flex,
+        _ => Display::None,
+    };
+}
+
+/// Cost and optimality reported by the solver, always shown.
+fn spawn_solution_metadata(
+    mut commands: Commands,
+    root: Single<Entity, With<HudRoot>>,
+    metadata: Res<SolutionMetadata>,
+) {
+    let cost = metadata.cost.as_deref().unwrap_or("n/a");
+    let optimal = match metadata.optimal {
+        Some(true) => "yes",
+        Some(false) => "no",
+        None => "n/a",
+    };
+    commands
+        .entity(*root)
+        .with_child(hud_line(format!("cost: {cost}  optimal: {optimal}"), true));
+}
+
+/// Live count of spawned entities and loaded assets, toggled with F4 by default.
+#[derive(Component)]
+struct EntityCountsText;
+
+fn spawn_entity_counts(mut commands: Commands, root: Single<Entity, With<HudRoot>>) {
+    commands.entity(*root).with_child((hud_line("", false), EntityCountsText));
+}
+
+fn toggle_entity_counts_system(
+    key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut query: Query<&mut Node, With<EntityCountsText>>,
+) {
+    if keymap.just_pressed(Action::EntityCounts, &key_input) {
+        for mut node in query.iter_mut() {
+            toggle_display(&mut node);
+        }
+    }
+}
+
+fn update_entity_counts_system(
+    blocks: Query<(), With<Block>>,
+    pipes: Query<(), With<Pipe>>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut query: Query<(&mut Text, &Node), With<EntityCountsText>>,
+) {
+    for (mut text, node) in query.iter_mut() {
+        if node.display == Display::None {
+            continue;
+        }
+
+        text.0 = format!(
+            "blocks: {}  pipes: {}  meshes: {}  materials: {}",
+            blocks.iter().count(),
+            pipes.iter().count(),
+            meshes.len(),
+            materials.len(),
+        );
+    }
+}
+
+/// Bounding box volume and exposed faces of the filled cells, shown with
+/// `--shape-stats`.
+#[derive(Component)]
+struct ShapeStatsText;
+
+fn spawn_shape_stats(mut commands: Commands, root: Single<Entity, With<HudRoot>>, config: Res<Config>) {
+    commands
+        .entity(*root)
+        .with_child((hud_line("", config.shape_stats), ShapeStatsText));
+}
+
+// Only recomputed when a new solution is loaded.
+fn update_shape_stats_system(positions: Res<Positions>, mut query: Query<&mut Text, With<ShapeStatsText>>) {
+    if !positions.is_changed() {
+        return;
+    }
+
+    for mut text in query.iter_mut() {
+        text.0 = format!(
+            "bounding box: {} cells  exposed faces: {}",
+            stats::bounding_volume(&positions),
+            stats::exposed_faces(&positions),
+        );
+    }
+}
+
+/// Every action with its current binding, toggled with F1 by default.
+#[derive(Component)]
+struct HelpControlsText;
+
+// Built from the keymap, so it lists whatever the user rebound.
+fn spawn_help_controls(
+    mut commands: Commands,
+    root: Single<Entity, With<HudRoot>>,
+    keymap: Res<Keymap>,
+    config: Res<Config>,
+) {
+    let width = Action::ALL.iter().map(|action| action.name().len()).max().unwrap_or(0);
+    let mut help = String::from("controls");
+    for action in Action::ALL {
+        help.push_str(&format!("\n  {:width$}  {:?}", action.name(), keymap.key(action)));
+    }
+
+    commands
+        .entity(*root)
+        .with_child((hud_line(help, config.help_controls), HelpControlsText));
+}
+
+fn toggle_help_controls_system(
+    key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut query: Query<&mut Node, With<HelpControlsText>>,
+) {
+    if keymap.just_pressed(Action::HelpControls, &key_input) {
+        for mut node in query.iter_mut() {
+            toggle_display(&mut node);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! `--id-textures`: prints each block's id on every face of its cubes, as
+//! a small generated texture on the block material, to tell blocks apart
+//! without relying on color alone.
+//!
+//! Text is drawn with a built-in 5×7 pixel font, scaled up as far as the id
+//! fits; lowercase letters are shown as capitals and anything else as `?`.
+//! The texture is white behind dark text, so multiplied by the block color
+//! it keeps the face in that color. Textures are made once per id and
+//! reused when `--watch` or blink respawns the blocks.
+
+use std::collections::HashMap;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::BlockMaterials;
+
+const TEXTURE_SIZE: usize = 64;
+/// Blank border around the text, in texture pixels.
+const PADDING: usize = 6;
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+const BACKGROUND: [u8; 4] = [255, 255, 255, 255];
+const INK: [u8; 4] = [32, 32, 32, 255];
+
+pub struct IdTexturesPlugin;
+
+impl Plugin for IdTexturesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<IdTextures>()
+            .add_systems(Update, apply_id_textures_system);
+    }
+}
+
+#[derive(Resource, Default)]
+struct IdTextures {
+    by_id: HashMap<String, Handle<Image>>,
+}
+
+/// Rows of a glyph from the top, the leftmost pixel in bit 4.
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    match c.to_ascii_uppercase() {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
+
+/// The id centered on a square texture, as large as fits.
+fn id_texture(id: &str) -> Image {
+    let chars: Vec<char> = id.chars().collect();
+    // Glyphs are one pixel apart at scale 1.
+    let text_width = (chars.len() * (GLYPH_WIDTH + 1)).saturating_sub(1).max(1);
+    let room = TEXTURE_SIZE - 2 * PADDING;
+    let scale = (room / text_width).min(room / GLYPH_HEIGHT).max(1);
+    let left = TEXTURE_SIZE.saturating_sub(text_width * scale) / 2;
+    let top = (TEXTURE_SIZE - GLYPH_HEIGHT * scale) / 2;
+
+    let mut data = BACKGROUND.repeat(TEXTURE_SIZE * TEXTURE_SIZE);
+    for (i, &c) in chars.iter().enumerate() {
+        for (row, bits) in glyph(c).into_iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - column)) == 0 {
+                    continue;
+                }
+                let x0 = left + (i * (GLYPH_WIDTH + 1) + column) * scale;
+                let y0 = top + row * scale;
+                // Ids too long for the texture are cut off on the right.
+                for y in y0..y0 + scale {
+                    for x in (x0..x0 + scale).filter(|&x| x < TEXTURE_SIZE) {
+                        let offset = (y * TEXTURE_SIZE + x) * 4;
+                        data[offset..offset + 4].copy_from_slice(&INK);
+                    }
+                }
+            }
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: TEXTURE_SIZE as u32,
+            height: TEXTURE_SIZE as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+fn apply_id_textures_system(
+    block_materials: Res<BlockMaterials>,
+    mut textures: ResMut<IdTextures>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !block_materials.is_changed() {
+        return;
+    }
+
+    let by_id = block_materials.by_id.iter();
+    for (id, handle) in by_id.chain(block_materials.striped.iter()) {
+        let texture = textures
+            .by_id
+            .entry(id.clone())
+            .or_insert_with(|| images.add(id_texture(id)));
+        if let Some(material) = materials.get_mut(handle)
+            && material.base_color_texture.is_none()
+        {
+            material.base_color_texture = Some(texture.clone());
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Reading solver output and other input files from disk.
+
+use flate2::read::MultiGzDecoder;
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Read},
+    path::Path,
+};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Reads a solution file, transparently decompressing it when it is gzipped
+/// (a `.gz` extension or the gzip magic bytes).
+pub fn read_solution_file(path: &Path) -> io::Result<String> {
+    let bytes = fs::read(path)?;
+    decode(bytes, path.extension().is_some_and(|ext| ext == "gz"))
+}
+
+/// Reads the solution piped to stdin until it is closed, decompressing it
+/// when it starts with the gzip magic bytes.
+pub fn read_solution_stdin() -> io::Result<String> {
+    let mut bytes = Vec::new();
+    io::stdin().lock().read_to_end(&mut bytes)?;
+    decode(bytes, false)
+}
+
+fn decode(bytes: Vec<u8>, gzipped: bool) -> io::Result<String> {
+    if gzipped || bytes.starts_with(&GZIP_MAGIC) {
+        let mut contents = String::new();
+        MultiGzDecoder::new(bytes.as_slice()).read_to_string(&mut contents)?;
+        Ok(contents)
+    } else {
+        String::from_utf8(bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+/// Drops a trailing atom the solver hasn't finished writing yet. Every
+/// model is printed on its own line, so output that doesn't end in a
+/// newline is still being written; the flag says whether that's the case.
+pub fn complete_part(contents: &str) -> (&str, bool) {
+    if contents.is_empty() || contents.ends_with('\n') {
+        return (contents, false);
+    }
+
+    // Only whitespace outside parentheses and quotes ends an atom, so a
+    // write cut off inside `block_pos(1,2,3,f(a)` still drops all of it.
+    let line = &contents[contents.rfind('\n').map_or(0, |newline| newline + 1)..];
+    let last = crate::split_terms(line, char::is_whitespace).last().unwrap_or_default();
+    if last.ends_with(')') && balanced(last) {
+        (contents, true)
+    } else {
+        (&contents[..contents.len() - last.len()], true)
+    }
+}
+
+/// Every parenthesis of `atom` outside quoted strings is closed.
+fn balanced(atom: &str) -> bool {
+    let mut depth = 0usize;
+    let mut quoted = false;
+    let mut escaped = false;
+    for c in atom.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => match depth.checked_sub(1) {
+                Some(closed) => depth = closed,
+                None => return false,
+            },
+            _ => (),
+        }
+    }
+    depth == 0 && !quoted
+}
+
+/// Cells listed one `x,y,z` per line, skipping blank lines and lines
+/// starting with `#`; the error is the number of the first bad line.
+pub fn parse_cell_list(contents: &str) -> Result<HashSet<(usize, usize, usize)>, usize> {
+    let mut cells = HashSet::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        cells.insert(crate::parse_cell(line).map_err(|_| index + 1)?);
+    }
+
+    Ok(cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn half_written_atoms_are_dropped() {
+        assert_eq!(complete_part("empty(1,1,1)\n"), ("empty(1,1,1)\n", false));
+        assert_eq!(complete_part("empty(1,1,1) block_pos(1,2,3,f(a)"), ("empty(1,1,1) ", true));
+        assert_eq!(complete_part("empty(1,1,1) block_pos(1,2"), ("empty(1,1,1) ", true));
+        assert_eq!(complete_part("empty(1,1,1) block_pos(1,1,1,\"red corner\",1"), ("empty(1,1,1) ", true));
+        assert_eq!(
+            complete_part("empty(1,1,1) block_pos(1,2,3,f(a),1)"),
+            ("empty(1,1,1) block_pos(1,2,3,f(a),1)", true)
+        );
+    }
+}
@@ -0,0 +1,291 @@
+//! `--instanced`: draws every block with one instanced draw call instead of
+//! one entity per cell, for boxes too large to spawn cell by cell.
+//!
+//! The blocks become a single entity carrying a list of per-instance
+//! positions and colors, rendered by a small custom pipeline with fixed
+//! lighting. Block entities are not spawned, so the overlays that act on
+//! them (hover, layer diff, pipe counts, hiding) have nothing to work on.
+//! Instances carry no rotation, so the blocks' orientation is left out;
+//! turning a unit cube by quarter turns doesn't change it anyway.
+
+use std::sync::Arc;
+
+use bevy::{
+    asset::load_internal_asset,
+    core_pipeline::core_3d::Transparent3d,
+    ecs::{
+        query::QueryItem,
+        system::{SystemParamItem, lifetimeless::*},
+    },
+    pbr::{MeshPipeline, MeshPipelineKey, RenderMeshInstances, SetMeshBindGroup, SetMeshViewBindGroup},
+    prelude::*,
+    render::{
+        Render, RenderApp, RenderSet,
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        mesh::{MeshVertexBufferLayoutRef, RenderMesh, RenderMeshBufferInfo, allocator::MeshAllocator},
+        render_asset::RenderAssets,
+        render_phase::{
+            AddRenderCommand, DrawFunctions, PhaseItem, PhaseItemExtraIndex, RenderCommand,
+            RenderCommandResult, SetItemPipeline, TrackedRenderPass, ViewSortedRenderPhases,
+        },
+        render_resource::*,
+        renderer::RenderDevice,
+        sync_world::MainEntity,
+        view::{ExtractedView, NoFrustumCulling},
+    },
+};
+use bytemuck::{Pod, Zeroable};
+
+const INSTANCED_BLOCKS_SHADER: Handle<Shader> =
+    Handle::weak_from_u128(0x3c1e_52a9_8f04_4b7d_9a6e_d2f1_07b8_c415);
+
+pub struct InstancingPlugin;
+
+impl Plugin for InstancingPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            INSTANCED_BLOCKS_SHADER,
+            "shaders/instanced_blocks.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.add_plugins(ExtractComponentPlugin::<InstancedBlocks>::default());
+        app.sub_app_mut(RenderApp)
+            .add_render_command::<Transparent3d, DrawInstancedBlocks>()
+            .init_resource::<SpecializedMeshPipelines<InstancedBlocksPipeline>>()
+            .add_systems(
+                Render,
+                (
+                    queue_instanced_blocks.in_set(RenderSet::QueueMeshes),
+                    prepare_instance_buffers.in_set(RenderSet::PrepareResources),
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        app.sub_app_mut(RenderApp)
+            .init_resource::<InstancedBlocksPipeline>();
+    }
+}
+
+/// Per-instance data, laid out to match the vertex attributes at locations
+/// 3 and 4 of the shader.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct BlockInstance {
+    pub position: Vec3,
+    pub scale: f32,
+    /// Linear RGBA.
+    pub color: [f32; 4],
+}
+
+// Shared so the per-frame extraction copies a pointer, not every instance.
+#[derive(Component, Clone, Deref)]
+pub struct InstancedBlocks(Arc<Vec<BlockInstance>>);
+
+impl ExtractComponent for InstancedBlocks {
+    type QueryData = &'static InstancedBlocks;
+    type QueryFilter = ();
+    type Out = Self;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self> {
+        Some(item.clone())
+    }
+}
+
+/// Bundle for the entity drawing all `instances` with the unit cube `mesh`.
+pub fn instanced_blocks(mesh: Handle<Mesh>, instances: Vec<BlockInstance>) -> impl Bundle {
+    (
+        Mesh3d(mesh),
+        InstancedBlocks(Arc::new(instances)),
+        // The mesh's own bounds say nothing about where the instances are.
+        NoFrustumCulling,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn queue_instanced_blocks(
+    draw_functions: Res<DrawFunctions<Transparent3d>>,
+    instanced_pipeline: Res<InstancedBlocksPipeline>,
+    mut pipelines: ResMut<SpecializedMeshPipelines<InstancedBlocksPipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    meshes: Res<RenderAssets<RenderMesh>>,
+    render_mesh_instances: Res<RenderMeshInstances>,
+    instanced: Query<(Entity, &MainEntity), With<InstancedBlocks>>,
+    mut phases: ResMut<ViewSortedRenderPhases<Transparent3d>>,
+    views: Query<(Entity, &ExtractedView, &Msaa)>,
+) {
+    let draw_function = draw_functions.read().id::<DrawInstancedBlocks>();
+
+    for (view_entity, view, msaa) in &views {
+        let Some(phase) = phases.get_mut(&view_entity) else {
+            continue;
+        };
+
+        let view_key =
+            MeshPipelineKey::from_msaa_samples(msaa.samples()) | MeshPipelineKey::from_hdr(view.hdr);
+        let rangefinder = view.rangefinder3d();
+
+        for (entity, main_entity) in &instanced {
+            let Some(mesh_instance) = render_mesh_instances.render_mesh_queue_data(*main_entity) else {
+                continue;
+            };
+            let Some(mesh) = meshes.get(mesh_instance.mesh_asset_id) else {
+                continue;
+            };
+
+            let key = view_key | MeshPipelineKey::from_primitive_topology(mesh.primitive_topology());
+            let pipeline = match pipelines.specialize(&pipeline_cache, &instanced_pipeline, key, &mesh.layout) {
+                Ok(pipeline) => pipeline,
+                Err(err) => {
+                    error!("Failed to specialize the instanced block pipeline: {err}");
+                    continue;
+                }
+            };
+
+            phase.add(Transparent3d {
+                entity: (entity, *main_entity),
+                pipeline,
+                draw_function,
+                distance: rangefinder.distance_translation(&mesh_instance.translation),
+                batch_range: 0..1,
+                extra_index: PhaseItemExtraIndex::NONE,
+            });
+        }
+    }
+}
+
+#[derive(Component)]
+struct InstanceBuffer {
+    buffer: Buffer,
+    length: usize,
+}
+
+fn prepare_instance_buffers(
+    mut commands: Commands,
+    query: Query<(Entity, &InstancedBlocks), Without<InstanceBuffer>>,
+    render_device: Res<RenderDevice>,
+) {
+    // The instances never change after startup, so upload them once.
+    for (entity, instances) in &query {
+        let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("instanced blocks buffer"),
+            contents: bytemuck::cast_slice(instances.as_slice()),
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+        });
+        commands.entity(entity).insert(InstanceBuffer {
+            buffer,
+            length: instances.len(),
+        });
+    }
+}
+
+#[derive(Resource)]
+struct InstancedBlocksPipeline {
+    mesh_pipeline: MeshPipeline,
+}
+
+impl FromWorld for InstancedBlocksPipeline {
+    fn from_world(world: &mut World) -> Self {
+        InstancedBlocksPipeline {
+            mesh_pipeline: world.resource::<MeshPipeline>().clone(),
+        }
+    }
+}
+
+impl SpecializedMeshPipeline for InstancedBlocksPipeline {
+    type Key = MeshPipelineKey;
+
+    fn specialize(
+        &self,
+        key: Self::Key,
+        layout: &MeshVertexBufferLayoutRef,
+    ) -> Result<RenderPipelineDescriptor, SpecializedMeshPipelineError> {
+        let mut descriptor = self.mesh_pipeline.specialize(key, layout)?;
+
+        descriptor.vertex.shader = INSTANCED_BLOCKS_SHADER;
+        descriptor.vertex.buffers.push(VertexBufferLayout {
+            array_stride: size_of::<BlockInstance>() as u64,
+            step_mode: VertexStepMode::Instance,
+            attributes: vec![
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: 0,
+                    shader_location: 3,
+                },
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: VertexFormat::Float32x4.size(),
+                    shader_location: 4,
+                },
+            ],
+        });
+        descriptor.fragment.as_mut().unwrap().shader = INSTANCED_BLOCKS_SHADER;
+        Ok(descriptor)
+    }
+}
+
+type DrawInstancedBlocks = (
+    SetItemPipeline,
+    SetMeshViewBindGroup<0>,
+    SetMeshBindGroup<1>,
+    DrawMeshInstanced,
+);
+
+struct DrawMeshInstanced;
+
+impl<P: PhaseItem> RenderCommand<P> for DrawMeshInstanced {
+    type Param = (
+        SRes<RenderAssets<RenderMesh>>,
+        SRes<RenderMeshInstances>,
+        SRes<MeshAllocator>,
+    );
+    type ViewQuery = ();
+    type ItemQuery = Read<InstanceBuffer>;
+
+    fn render<'w>(
+        item: &P,
+        _view: (),
+        instance_buffer: Option<&'w InstanceBuffer>,
+        (meshes, render_mesh_instances, mesh_allocator): SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let mesh_allocator = mesh_allocator.into_inner();
+
+        let Some(mesh_instance) = render_mesh_instances.render_mesh_queue_data(item.main_entity()) else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(gpu_mesh) = meshes.into_inner().get(mesh_instance.mesh_asset_id) else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(instance_buffer) = instance_buffer else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(vertex_slice) = mesh_allocator.mesh_vertex_slice(&mesh_instance.mesh_asset_id) else {
+            return RenderCommandResult::Skip;
+        };
+
+        pass.set_vertex_buffer(0, vertex_slice.buffer.slice(..));
+        pass.set_vertex_buffer(1, instance_buffer.buffer.slice(..));
+
+        let instances = 0..instance_buffer.length as u32;
+        match &gpu_mesh.buffer_info {
+            RenderMeshBufferInfo::Indexed { index_format, count } => {
+                let Some(index_slice) = mesh_allocator.mesh_index_slice(&mesh_instance.mesh_asset_id) else {
+                    return RenderCommandResult::Skip;
+                };
+                pass.set_index_buffer(index_slice.buffer.slice(..), 0, *index_format);
+                pass.draw_indexed(
+                    index_slice.range.start..(index_slice.range.start + count),
+                    vertex_slice.range.start as i32,
+                    instances,
+                );
+            }
+            RenderMeshBufferInfo::NonIndexed => {
+                pass.draw(vertex_slice.range, instances);
+            }
+        }
+        RenderCommandResult::Success
+    }
+}
@@ -0,0 +1,117 @@
+//! Pipe junctions: cells where the pipes connect to more than two faces,
+//! so the network branches (three faces, a T) or crosses (four or more)
+//! instead of running through. Each gets a sphere in place of a plain
+//! joint and the HUD counts them.
+//!
+//! A straight or bent pipe through a cell always pairs its faces up; a
+//! cell with an odd number of connected faces leaves one unpaired, which
+//! the generator shouldn't produce, so those are flagged in a different
+//! color and logged.
+
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+
+use crate::{
+    Config, Direction, Positions,
+    hud::{HudRoot, hud_line, spawn_hud_root},
+};
+
+/// Large enough to stand out around the pipe cross-section.
+const JUNCTION_RADIUS: f32 = 0.3;
+const JUNCTION_COLOR: Color = Color::srgb(1.0, 0.6, 0.1);
+const FLAGGED_COLOR: Color = Color::srgb(1.0, 0.1, 0.8);
+
+pub struct JunctionsPlugin;
+
+impl Plugin for JunctionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_junctions.after(spawn_hud_root))
+            .add_systems(Update, spawn_junctions_system);
+    }
+}
+
+#[derive(Resource)]
+struct JunctionAssets {
+    mesh: Handle<Mesh>,
+    junction: Handle<StandardMaterial>,
+    flagged: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+struct Junction;
+
+#[derive(Component)]
+struct JunctionsText;
+
+/// Faces a cell's pipes connect to, over all its segments.
+fn connections(segments: &[(Direction, Direction)]) -> BTreeSet<Direction> {
+    segments
+        .iter()
+        .flat_map(|&(in_dir, out_dir)| [in_dir, out_dir])
+        .collect()
+}
+
+fn setup_junctions(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    root: Option<Single<Entity, With<HudRoot>>>,
+) {
+    commands.insert_resource(JunctionAssets {
+        mesh: meshes.add(Sphere::new(JUNCTION_RADIUS)),
+        junction: materials.add(JUNCTION_COLOR),
+        flagged: materials.add(FLAGGED_COLOR),
+    });
+    if let Some(root) = root {
+        commands
+            .entity(*root)
+            .with_child((hud_line("", true), JunctionsText));
+    }
+}
+
+fn spawn_junctions_system(
+    mut commands: Commands,
+    positions: Res<Positions>,
+    config: Res<Config>,
+    assets: Res<JunctionAssets>,
+    spawned: Query<Entity, With<Junction>>,
+    mut text_query: Query<&mut Text, With<JunctionsText>>,
+) {
+    if !positions.is_changed() {
+        return;
+    }
+    for entity in spawned.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let mut junctions = 0;
+    let mut flagged = 0;
+    for (&(x, y, z), segments) in &positions.pipe_positions {
+        let count = connections(segments).len();
+        let odd = !count.is_multiple_of(2);
+        if odd {
+            warn!("Cell ({x},{y},{z}) has pipes to {count} faces, which can't pair up");
+            flagged += 1;
+        }
+        if count <= 2 && !odd {
+            continue;
+        }
+        junctions += usize::from(count > 2);
+
+        commands.spawn((
+            Mesh3d(assets.mesh.clone()),
+            MeshMaterial3d(if odd {
+                assets.flagged.clone()
+            } else {
+                assets.junction.clone()
+            }),
+            Transform::from_translation(config.cell_position((x, y, z))),
+            Junction,
+        ));
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.0 = format!("pipe junctions: {junctions}, odd connection counts: {flagged}");
+    }
+}
@@ -0,0 +1,300 @@
+//! Named input actions and their key bindings.
+//!
+//! Bindings can be overridden with `--keymap <file>`, a plain text file with
+//! one `action = KeyCode` pair per line, e.g. `toggle_hide = KeyJ`. Blank
+//! lines and lines starting with `#` are ignored; actions not listed keep
+//! their default key.
+
+use bevy::prelude::*;
+use std::{collections::HashMap, fmt, fs, io, path::Path};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    CameraControls,
+    Hide,
+    HidePipes,
+    EntityCounts,
+    LayerDiff,
+    PipeCounts,
+    ScaleReference,
+    DropBlocks,
+    HelpControls,
+    BlockChart,
+    PipeFocus,
+    Normals,
+    NextSolution,
+    StepActive,
+    StraightRun,
+    Pip,
+    SnapAngle,
+    LayerUp,
+    LayerDown,
+    LayerSlice,
+    Carve,
+    UndoCarve,
+    PipeTube,
+    Footprint,
+    FocusMode,
+    FocusGrow,
+    FocusShrink,
+    Screenshot,
+    PlayPause,
+    ScrubForward,
+    ScrubBack,
+    Axes,
+    GrowDimension,
+    GrowBox,
+    ShrinkBox,
+    Wireframe,
+    SaveState,
+    Transparent,
+    MoreOpaque,
+    LessOpaque,
+}
+
+impl Action {
+    pub const ALL: [Action; 40] = [
+        Action::CameraControls,
+        Action::Hide,
+        Action::HidePipes,
+        Action::EntityCounts,
+        Action::LayerDiff,
+        Action::PipeCounts,
+        Action::ScaleReference,
+        Action::DropBlocks,
+        Action::HelpControls,
+        Action::BlockChart,
+        Action::PipeFocus,
+        Action::Normals,
+        Action::NextSolution,
+        Action::StepActive,
+        Action::StraightRun,
+        Action::Pip,
+        Action::SnapAngle,
+        Action::LayerUp,
+        Action::LayerDown,
+        Action::LayerSlice,
+        Action::Carve,
+        Action::UndoCarve,
+        Action::PipeTube,
+        Action::Footprint,
+        Action::FocusMode,
+        Action::FocusGrow,
+        Action::FocusShrink,
+        Action::Screenshot,
+        Action::PlayPause,
+        Action::ScrubForward,
+        Action::ScrubBack,
+        Action::Axes,
+        Action::GrowDimension,
+        Action::GrowBox,
+        Action::ShrinkBox,
+        Action::Wireframe,
+        Action::SaveState,
+        Action::Transparent,
+        Action::MoreOpaque,
+        Action::LessOpaque,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::CameraControls => "toggle_camera_controls",
+            Action::Hide => "toggle_hide",
+            Action::HidePipes => "toggle_pipes",
+            Action::EntityCounts => "toggle_entity_counts",
+            Action::LayerDiff => "toggle_layer_diff",
+            Action::PipeCounts => "toggle_pipe_counts",
+            Action::ScaleReference => "toggle_scale_reference",
+            Action::DropBlocks => "drop_blocks",
+            Action::HelpControls => "toggle_help_controls",
+            Action::BlockChart => "toggle_block_chart",
+            Action::PipeFocus => "toggle_pipe_focus",
+            Action::Normals => "toggle_normals",
+            Action::NextSolution => "next_solution",
+            Action::StepActive => "step_active_along_path",
+            Action::StraightRun => "toggle_straight_run",
+            Action::Pip => "toggle_pip",
+            Action::SnapAngle => "toggle_snap_angle",
+            Action::LayerUp => "layer_up",
+            Action::LayerDown => "layer_down",
+            Action::LayerSlice => "toggle_layer_slice",
+            Action::Carve => "carve_block",
+            Action::UndoCarve => "undo_carve",
+            Action::PipeTube => "toggle_pipe_tube",
+            Action::Footprint => "toggle_footprint",
+            Action::FocusMode => "toggle_focus_mode",
+            Action::FocusGrow => "grow_focus",
+            Action::FocusShrink => "shrink_focus",
+            Action::Screenshot => "save_screenshot",
+            Action::PlayPause => "toggle_playback",
+            Action::ScrubForward => "scrub_forward",
+            Action::ScrubBack => "scrub_back",
+            Action::Axes => "toggle_axes",
+            Action::GrowDimension => "grow_dimension",
+            Action::GrowBox => "grow_box",
+            Action::ShrinkBox => "shrink_box",
+            Action::Wireframe => "toggle_wireframe",
+            Action::SaveState => "save_state",
+            Action::Transparent => "toggle_transparent",
+            Action::MoreOpaque => "more_opaque",
+            Action::LessOpaque => "less_opaque",
+        }
+    }
+
+    fn default_key(&self) -> KeyCode {
+        match self {
+            Action::CameraControls => KeyCode::KeyT,
+            Action::Hide => KeyCode::KeyB,
+            Action::HidePipes => KeyCode::KeyP,
+            Action::EntityCounts => KeyCode::F4,
+            Action::LayerDiff => KeyCode::KeyD,
+            Action::PipeCounts => KeyCode::KeyC,
+            Action::ScaleReference => KeyCode::KeyR,
+            Action::DropBlocks => KeyCode::KeyX,
+            Action::HelpControls => KeyCode::F1,
+            Action::BlockChart => KeyCode::KeyN,
+            Action::PipeFocus => KeyCode::KeyF,
+            Action::Normals => KeyCode::F3,
+            Action::NextSolution => KeyCode::KeyS,
+            Action::StepActive => KeyCode::KeyV,
+            Action::StraightRun => KeyCode::KeyL,
+            Action::Pip => KeyCode::KeyI,
+            Action::SnapAngle => KeyCode::KeyA,
+            Action::LayerUp => KeyCode::ArrowUp,
+            Action::LayerDown => KeyCode::ArrowDown,
+            Action::LayerSlice => KeyCode::KeyY,
+            Action::Carve => KeyCode::Delete,
+            Action::UndoCarve => KeyCode::KeyU,
+            Action::PipeTube => KeyCode::KeyK,
+            Action::Footprint => KeyCode::KeyJ,
+            Action::FocusMode => KeyCode::KeyO,
+            Action::FocusGrow => KeyCode::BracketRight,
+            Action::FocusShrink => KeyCode::BracketLeft,
+            Action::Screenshot => KeyCode::F12,
+            Action::PlayPause => KeyCode::Space,
+            Action::ScrubForward => KeyCode::Period,
+            Action::ScrubBack => KeyCode::Comma,
+            Action::Axes => KeyCode::KeyE,
+            Action::GrowDimension => KeyCode::Tab,
+            Action::GrowBox => KeyCode::PageUp,
+            Action::ShrinkBox => KeyCode::PageDown,
+            Action::Wireframe => KeyCode::KeyG,
+            Action::SaveState => KeyCode::F5,
+            Action::Transparent => KeyCode::KeyM,
+            Action::MoreOpaque => KeyCode::Equal,
+            Action::LessOpaque => KeyCode::Minus,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct Keymap {
+    bindings: HashMap<Action, KeyCode>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap {
+            bindings: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_key()))
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    pub fn load(path: &Path) -> Result<Keymap, KeymapError> {
+        let contents = fs::read_to_string(path).map_err(KeymapError::Io)?;
+        Keymap::parse(&contents)
+    }
+
+    fn parse(contents: &str) -> Result<Keymap, KeymapError> {
+        let mut keymap = Keymap::default();
+
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let line_number = index + 1;
+            let (name, key) = line
+                .split_once('=')
+                .map(|(name, key)| (name.trim(), key.trim()))
+                .ok_or(KeymapError::Malformed { line: line_number })?;
+            let action = Action::from_name(name).ok_or_else(|| KeymapError::UnknownAction {
+                line: line_number,
+                name: name.to_string(),
+            })?;
+            let key = parse_key_code(key).ok_or_else(|| KeymapError::UnknownKey {
+                line: line_number,
+                name: key.to_string(),
+            })?;
+            keymap.bindings.insert(action, key);
+        }
+
+        Ok(keymap)
+    }
+
+    pub fn key(&self, action: Action) -> KeyCode {
+        self.bindings[&action]
+    }
+
+    pub fn just_pressed(&self, action: Action, key_input: &ButtonInput<KeyCode>) -> bool {
+        key_input.just_pressed(self.key(action))
+    }
+}
+
+#[derive(Debug)]
+pub enum KeymapError {
+    Io(io::Error),
+    Malformed { line: usize },
+    UnknownAction { line: usize, name: String },
+    UnknownKey { line: usize, name: String },
+}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeymapError::Io(err) => write!(f, "{err}"),
+            KeymapError::Malformed { line } => {
+                write!(f, "line {line}: expected `action = KeyCode`")
+            }
+            KeymapError::UnknownAction { line, name } => {
+                write!(f, "line {line}: unknown action `{name}`")
+            }
+            KeymapError::UnknownKey { line, name } => {
+                write!(f, "line {line}: unknown key `{name}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeymapError {}
+
+// Key names are spelled like the `KeyCode` variants they map to.
+macro_rules! key_codes {
+    ($($name:ident),* $(,)?) => {
+        fn parse_key_code(name: &str) -> Option<KeyCode> {
+            match name {
+                $(stringify!($name) => Some(KeyCode::$name),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+key_codes!(
+    KeyA, KeyB, KeyC, KeyD, KeyE, KeyF, KeyG, KeyH, KeyI, KeyJ, KeyK, KeyL, KeyM, KeyN, KeyO,
+    KeyP, KeyQ, KeyR, KeyS, KeyT, KeyU, KeyV, KeyW, KeyX, KeyY, KeyZ, Digit0, Digit1, Digit2,
+    Digit3, Digit4, Digit5, Digit6, Digit7, Digit8, Digit9, F1, F2, F3, F4, F5, F6, F7, F8, F9,
+    F10, F11, F12, ArrowUp, ArrowDown, ArrowLeft, ArrowRight, Space, Enter, Escape, Tab,
+    Backspace, Delete, Insert, Home, End, PageUp, PageDown, Minus, Equal, BracketLeft,
+    BracketRight, Backslash, Semicolon, Quote, Comma, Period, Slash, Backquote, NumpadAdd,
+    NumpadSubtract,
+);
@@ -0,0 +1,105 @@
+//! Highlights cells of the active layer whose block differs from the cell
+//! directly below, showing where the packing changes between layers.
+
+use bevy::prelude::*;
+
+use crate::{
+    ActiveLayer, Positions,
+    hud::{HudRoot, hud_line, spawn_hud_root},
+    keymap::{Action, Keymap},
+    overlays::BlockMaterialSet,
+};
+
+pub struct LayerDiffPlugin;
+
+impl Plugin for LayerDiffPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_layer_diff.after(spawn_hud_root))
+            .add_systems(
+                Update,
+                (toggle_layer_diff_system, show_layer_diff_system)
+                    .chain()
+                    .before(BlockMaterialSet::Overlays),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct LayerDiff {
+    pub enabled: bool,
+    highlight: Handle<StandardMaterial>,
+}
+
+impl LayerDiff {
+    /// The highlight, for a cell of the active layer whose block differs
+    /// from the one below while shown.
+    pub fn material(
+        &self,
+        positions: &Positions,
+        active_layer: &ActiveLayer,
+        cell: (usize, usize, usize),
+    ) -> Option<&Handle<StandardMaterial>> {
+        (self.enabled && differs(positions, active_layer, cell)).then_some(&self.highlight)
+    }
+}
+
+fn differs(positions: &Positions, active_layer: &ActiveLayer, (x, y, z): (usize, usize, usize)) -> bool {
+    y == active_layer.y && y > 1 && positions.block_id(&(x, y - 1, z)) != positions.block_id(&(x, y, z))
+}
+
+#[derive(Component)]
+struct LayerDiffText;
+
+fn setup_layer_diff(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    root: Single<Entity, With<HudRoot>>,
+) {
+    commands.insert_resource(LayerDiff {
+        enabled: false,
+        highlight: materials.add(StandardMaterial {
+            base_color: Color::srgb(1.0, 0.85, 0.0),
+            emissive: LinearRgba::rgb(0.6, 0.5, 0.0),
+            ..default()
+        }),
+    });
+    commands.entity(*root).with_child((hud_line("", false), LayerDiffText));
+}
+
+fn toggle_layer_diff_system(
+    key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut layer_diff: ResMut<LayerDiff>,
+) {
+    if keymap.just_pressed(Action::LayerDiff, &key_input) {
+        layer_diff.enabled = !layer_diff.enabled;
+    }
+}
+
+fn show_layer_diff_system(
+    layer_diff: Res<LayerDiff>,
+    active_layer: Res<ActiveLayer>,
+    positions: Res<Positions>,
+    mut text_query: Query<(&mut Text, &mut Node), With<LayerDiffText>>,
+) {
+    if !layer_diff.is_changed() && !active_layer.is_changed() {
+        return;
+    }
+
+    let differing = positions
+        .positions
+        .keys()
+        .filter(|&&cell| differs(&positions, &active_layer, cell))
+        .count();
+    for (mut text, mut node) in text_query.iter_mut() {
+        node.display = if layer_diff.enabled {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        text.0 = format!(
+            "layer diff: {differing} cells of layer {} differ from the layer below",
+            active_layer.y
+        );
+    }
+}
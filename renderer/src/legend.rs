@@ -0,0 +1,152 @@
+//! Headless legend export: one color swatch and label per block id, laid
+//! out in columns and saved as a PNG for use next to a figure.
+
+use std::path::PathBuf;
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        view::screenshot::save_to_disk,
+    },
+};
+
+use crate::{
+    BlockMaterials,
+    offscreen::{self, FrameSequence, FrameStep, PipelinesReady},
+};
+
+/// Entries per column before wrapping into the next one.
+const LEGEND_ROWS: usize = 16;
+const ROW_HEIGHT: f32 = 28.0;
+const COLUMN_WIDTH: f32 = 140.0;
+const SWATCH_SIZE: f32 = 20.0;
+const PADDING: f32 = 12.0;
+
+pub struct LegendPlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for LegendPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Legend { path: self.path.clone() })
+            .add_systems(Startup, spawn_legend.after(crate::setup))
+            .add_systems(Update, capture_legend);
+    }
+}
+
+#[derive(Resource)]
+struct Legend {
+    path: PathBuf,
+}
+
+#[derive(Component)]
+struct LegendCamera {
+    target: Handle<Image>,
+}
+
+/// Numeric ids in numeric order, anything else after them.
+pub fn sorted_ids<'a>(ids: impl Iterator<Item = &'a String>) -> Vec<&'a String> {
+    let mut ids: Vec<&String> = ids.collect();
+    ids.sort_by_key(|id| (id.parse::<usize>().unwrap_or(usize::MAX), *id));
+    ids
+}
+
+fn spawn_legend(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    block_materials: Res<BlockMaterials>,
+    materials: Res<Assets<StandardMaterial>>,
+) {
+    let ids = sorted_ids(block_materials.by_id.keys());
+
+    let rows = ids.len().clamp(1, LEGEND_ROWS);
+    let columns = ids.len().div_ceil(LEGEND_ROWS).max(1);
+    let width = columns as f32 * COLUMN_WIDTH + 2.0 * PADDING;
+    let height = rows as f32 * ROW_HEIGHT + 2.0 * PADDING;
+    let target = offscreen::render_target(&mut images, width as u32, height as u32);
+
+    let camera = commands
+        .spawn((
+            Camera2d,
+            Camera {
+                target: RenderTarget::Image(target.clone()),
+                clear_color: ClearColorConfig::Custom(Color::WHITE),
+                ..default()
+            },
+            LegendCamera { target },
+            FrameSequence::new(1),
+        ))
+        .id();
+
+    // Filled top to bottom, then left to right.
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(PADDING)),
+                flex_direction: FlexDirection::Column,
+                flex_wrap: FlexWrap::Wrap,
+                ..default()
+            },
+            TargetCamera(camera),
+        ))
+        .with_children(|parent| {
+            for id in ids {
+                let color = materials
+                    .get(block_materials.by_id.get(id).unwrap_or(&block_materials.fallback))
+                    .map_or(Color::BLACK, |material| material.base_color);
+
+                parent
+                    .spawn(Node {
+                        width: Val::Px(COLUMN_WIDTH),
+                        height: Val::Px(ROW_HEIGHT),
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(8.0),
+                        ..default()
+                    })
+                    .with_children(|entry| {
+                        entry.spawn((
+                            Node {
+                                width: Val::Px(SWATCH_SIZE),
+                                height: Val::Px(SWATCH_SIZE),
+                                border: UiRect::all(Val::Px(1.0)),
+                                ..default()
+                            },
+                            BackgroundColor(color),
+                            BorderColor(Color::BLACK),
+                        ));
+                        entry.spawn((
+                            Text::new(format!("block {id}")),
+                            TextFont {
+                                font_size: 16.0,
+                                ..default()
+                            },
+                            TextColor(Color::BLACK),
+                        ));
+                    });
+            }
+        });
+}
+
+fn capture_legend(
+    mut commands: Commands,
+    legend: Res<Legend>,
+    ready: Res<PipelinesReady>,
+    camera: Single<(Entity, &LegendCamera, &mut FrameSequence)>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let (entity, legend_camera, mut sequence) = camera.into_inner();
+    match sequence.step(&ready) {
+        FrameStep::Wait | FrameStep::Pose(_) => (),
+        FrameStep::Capture(_) => {
+            offscreen::capture(&mut commands, entity, &legend_camera.target)
+                .observe(save_to_disk(legend.path.clone()));
+        }
+        FrameStep::Done => {
+            info!("Legend: wrote {}", legend.path.display());
+            exit.send(AppExit::Success);
+        }
+    }
+}
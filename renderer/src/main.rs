@@ -6,12 +6,143 @@
 //!   Pan: Shift + Middle click
 //!   Zoom: Mousewheel
 
-use bevy::prelude::*;
+mod active;
+mod anaglyph;
+mod axes;
+mod binary;
+mod blink;
+mod block_chart;
+mod bundle;
+mod camera;
+mod carve;
+mod compare;
+mod depth;
+mod drop;
+mod environment;
+mod faces;
+mod focus;
+mod footprint;
+mod grow;
+mod hover;
+mod hud;
+mod id_textures;
+mod input;
+mod instancing;
+mod junctions;
+mod keymap;
+mod layer_diff;
+mod legend;
+mod normals;
+mod obj;
+mod offscreen;
+mod overlays;
+mod query;
+mod radial;
+mod pipe_caps;
+mod pipe_counts;
+mod pipe_flow;
+mod pipe_focus;
+mod pipe_tube;
+mod pipe_mesh;
+mod pipe_pairs;
+mod pipe_path;
+mod pip;
+mod playback;
+mod recovery;
+mod scalar_field;
+mod scale_reference;
+mod screenshot;
+mod section_sweep;
+mod snap;
+mod state;
+mod stats;
+mod straight_run;
+mod target;
+mod transparent;
+mod turntable;
+mod watch;
+mod web;
+mod wireframe;
+
+use bevy::{
+    app::ScheduleRunnerPlugin,
+    log::{Level, LogPlugin},
+    asset::RenderAssetUsages,
+    pbr::wireframe::WireframePlugin,
+    image::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor},
+    math::Affine2,
+    prelude::*,
+    render::{
+        RenderPlugin,
+        render_resource::{Extent3d, TextureDimension, TextureFormat, WgpuFeatures},
+        renderer::RenderAdapter,
+        settings::WgpuSettings,
+    },
+    window::ExitCondition,
+    winit::WinitPlugin,
+};
 use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin, TouchControls};
-use rand::Rng;
-use std::{collections::HashMap, f32::consts::TAU, process::Command, str::FromStr};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use std::{
+    collections::{HashMap, HashSet},
+    f32::consts::{FRAC_PI_2, TAU},
+    fmt,
+    num::ParseIntError,
+    path::PathBuf,
+    process::Command,
+    str::FromStr,
+    time::Duration,
+};
+use active::ActivePlugin;
+use anaglyph::AnaglyphPlugin;
+use axes::AxesPlugin;
+use blink::BlinkPlugin;
+use block_chart::BlockChartPlugin;
+use bundle::{Bundle, SolutionJson};
+use carve::CarvePlugin;
+use compare::ComparePlugin;
+use depth::DepthPlugin;
+use drop::DropPlugin;
+use environment::{Environment, EnvironmentPlugin};
+use faces::FacesPlugin;
+use focus::FocusPlugin;
+use footprint::FootprintPlugin;
+use grow::GrowPlugin;
+use hover::HoverPlugin;
+use hud::HudPlugin;
+use id_textures::IdTexturesPlugin;
+use instancing::{BlockInstance, InstancedBlocks, InstancingPlugin};
+use junctions::JunctionsPlugin;
+use keymap::{Action, Keymap};
+use layer_diff::LayerDiffPlugin;
+use legend::LegendPlugin;
+use normals::NormalsPlugin;
+use offscreen::OffscreenPlugin;
+use overlays::OverlaysPlugin;
+use pipe_caps::{CapStyle, PipeCapsPlugin};
+use pipe_counts::PipeCountsPlugin;
+use pipe_flow::PipeFlow;
+use pipe_focus::PipeFocusPlugin;
+use pipe_tube::PipeTubePlugin;
+use pipe_pairs::PipePairsPlugin;
+use pip::{PipCorner, PipPlugin};
+use playback::PlaybackPlugin;
+use target::{TargetMask, TargetPlugin};
+use transparent::TransparentPlugin;
+use radial::DistanceMetric;
+use recovery::RecoveryPlugin;
+use scalar_field::{ScalarField, ScalarFieldPlugin};
+use screenshot::ScreenshotPlugin;
+use section_sweep::{SectionAxis, SectionSweepPlugin};
+use snap::SnapPlugin;
+use state::{State, StatePlugin};
+use straight_run::StraightRunPlugin;
+use turntable::TurntablePlugin;
+use watch::WatchPlugin;
+use wireframe::WireframeModePlugin;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 enum Direction {
     Above,
     Below,
@@ -22,6 +153,31 @@ enum Direction {
 }
 
 impl Direction {
+    /// The six directions in declaration order, which is also the order of
+    /// their bytes in the binary format.
+    fn all() -> impl Iterator<Item = Direction> {
+        [
+            Direction::Above,
+            Direction::Below,
+            Direction::North,
+            Direction::South,
+            Direction::East,
+            Direction::West,
+        ]
+        .into_iter()
+    }
+
+    fn opposite(&self) -> Direction {
+        match self {
+            Direction::Above => Direction::Below,
+            Direction::Below => Direction::Above,
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+        }
+    }
+
     fn pipe_width(&self) -> f32 {
         match self {
             Direction::Above | Direction::Below => 0.3,
@@ -56,6 +212,34 @@ impl Direction {
             Direction::West => Transform::from_xyz(x - 0.175, y, z),
         }
     }
+
+    /// Turns the Y-up axis of a default cylinder onto this direction.
+    fn pipe_rotation(&self) -> Quat {
+        Quat::from_rotation_arc(Vec3::Y, self.offset())
+    }
+
+    /// Unit step from a cell to its neighbor on this side.
+    fn offset(&self) -> Vec3 {
+        match self {
+            Direction::Above => Vec3::Y,
+            Direction::Below => Vec3::NEG_Y,
+            Direction::North => Vec3::Z,
+            Direction::South => Vec3::NEG_Z,
+            Direction::East => Vec3::X,
+            Direction::West => Vec3::NEG_X,
+        }
+    }
+
+    fn on_face(&self, x: usize, y: usize, z: usize, config: &Config) -> bool {
+        match self {
+            Direction::Above => y == config.height,
+            Direction::Below => y == 1,
+            Direction::North => z == config.depth,
+            Direction::South => z == 1,
+            Direction::East => x == config.width,
+            Direction::West => x == 1,
+        }
+    }
 }
 
 impl FromStr for Direction {
@@ -74,15 +258,71 @@ impl FromStr for Direction {
     }
 }
 
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Direction::Above => "above",
+            Direction::Below => "below",
+            Direction::North => "north",
+            Direction::South => "south",
+            Direction::East => "east",
+            Direction::West => "west",
+        })
+    }
+}
+
 #[derive(Resource)]
 struct ActiveLayer {
     y: usize,
+    /// Only the active layer is shown.
+    sliced: bool,
 }
 
-#[derive(Resource)]
+/// The block filling a cell, as `block_pos` places it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BlockInfo {
+    id: String,
+    /// Which of the block's unit cubes this is, numbered from 1 like in
+    /// its definition. The solver places the first and derives the others
+    /// from the block's rotation.
+    unit: u32,
+    orientation: Orientation,
+}
+
+/// How the solver turned a block from its definition, in quarter turns
+/// about the x, y and z axes (`rot(Axis, B, R)`, shown by `solver.lp`),
+/// applied in that order. None for output without `rot` atoms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Orientation([u8; 3]);
+
+impl Orientation {
+    /// The turn of the block in world space.
+    ///
+    /// The solver turns sides as `rot_dir` lists them: a quarter turn about
+    /// x takes above to south, about y north to east, and about z above to
+    /// east. With east +X, above +Y and north +Z, those are -90° about X,
+    /// +90° about Y and -90° about Z. Composed x first, the 64 combinations
+    /// land on the 24 rotations of the cube, several on the same one.
+    fn rotation(&self) -> Quat {
+        let [x, y, z] = self.0.map(|turns| f32::from(turns) * FRAC_PI_2);
+        Quat::from_rotation_z(-z) * Quat::from_rotation_y(y) * Quat::from_rotation_x(-x)
+    }
+}
+
+#[derive(Resource, Clone, Debug, PartialEq)]
 struct Positions {
-    positions: HashMap<(usize, usize, usize), String>,
-    pipe_positions: HashMap<(usize, usize, usize), (Direction, Direction)>,
+    positions: HashMap<(usize, usize, usize), BlockInfo>,
+    pipe_positions: HashMap<(usize, usize, usize), Vec<(Direction, Direction)>>,
+    /// Cells the solver explicitly left empty, as opposed to cells it said
+    /// nothing about.
+    empty: HashSet<(usize, usize, usize)>,
+}
+
+impl Positions {
+    /// Id of the block filling `cell`, if any.
+    fn block_id(&self, cell: &(usize, usize, usize)) -> Option<&String> {
+        self.positions.get(cell).map(|block| &block.id)
+    }
 }
 
 #[derive(Resource, Debug)]
@@ -90,43 +330,396 @@ struct Config {
     width: usize,
     height: usize,
     depth: usize,
+    /// World offset of the whole box.
+    origin: Vec3,
     hide: bool,
+    hide_pipes: bool,
+    /// Pipes are spawned at all; off with `--no-pipes`.
+    pipes: bool,
+    face: Option<Direction>,
+    flat_shading: bool,
+    msaa: Msaa,
+    scale_reference: bool,
+    floor: bool,
+    /// Largest random tilt of each cube around every axis, in degrees.
+    jitter_rotation: Option<f32>,
+    /// Picks the block palette and seeds the jitter, random on every run if
+    /// unset.
+    seed: Option<u64>,
+    /// Distinct hues block colors are picked from.
+    color_buckets: usize,
+    color_mode: ColorMode,
+    /// How `ColorMode::Radial` measures the distance from the center.
+    distance_metric: DistanceMetric,
+    help_controls: bool,
+    /// The HUD shows the bounding box volume and exposed faces.
+    shape_stats: bool,
+    instanced: bool,
+    pipe_colors: PipeColors,
+    pipe_style: PipeStyle,
+    pipe_shape: PipeShape,
+    pipe_radius: f32,
+    pipe_quality: PipeQuality,
+    /// Per-segment values thickening the pipe pieces, with `--pipe-flow`.
+    pipe_flow: Option<PipeFlow>,
+    /// Blocks drawn as wireframes so the pipes stand out.
+    pipe_focus: bool,
+    /// Only the blocks the pipe passes through are drawn.
+    pipe_tube: bool,
+    /// Blocks hidden by the pipe tube are outlined.
+    tube_context: bool,
+    wireframe_color: Color,
+    /// Colors of the X, Y and Z axes.
+    axis_colors: [Color; 3],
+    scalar_field: Option<ScalarField>,
+    /// How much darker blocks on even layers are drawn.
+    layer_stripe: Option<f32>,
+    /// Vertical field of view of every perspective camera, in radians.
+    fov: f32,
+}
+
+impl Config {
+    /// World position of the center of cell `(x, y, z)`.
+    fn cell_position(&self, (x, y, z): (usize, usize, usize)) -> Vec3 {
+        self.origin + Vec3::new(x as f32, y as f32, z as f32)
+    }
+
+    /// Half the thickness of a pipe segment.
+    fn joint_radius(&self) -> f32 {
+        match self.pipe_shape {
+            PipeShape::Cuboid => 0.15,
+            PipeShape::Cylinder => self.pipe_radius,
+        }
+    }
+
+    /// Pipes are one mitered mesh per segment, already joined. A mitered
+    /// elbow can't be thickened one side at a time, so flow values draw
+    /// separate pieces.
+    fn mitered_pipes(&self) -> bool {
+        self.pipe_style == PipeStyle::Stub
+            && self.pipe_shape == PipeShape::Cuboid
+            && self.pipe_quality == PipeQuality::High
+            && self.pipe_flow.is_none()
+    }
+
+    /// Thickness of the pipe piece leaving `cell` through `dir`, relative
+    /// to the usual one.
+    fn pipe_thickness(&self, cell: (usize, usize, usize), dir: Direction) -> f32 {
+        self.pipe_flow.as_ref().map_or(1.0, |flow| flow.thickness(cell, dir))
+    }
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum PipeStyle {
+    /// Stubs from the cell center to each face it connects
+    Stub,
+    /// Bars from the cell center to each connected neighbor's center
+    Span,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum PipeShape {
+    /// Square bars
+    Cuboid,
+    /// Round tubes along the segment's axis
+    Cylinder,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum PipeQuality {
+    /// Two overlapping cuboids per segment
+    Low,
+    /// One mesh per segment with mitered elbows
+    High,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum SolutionFormat {
+    /// Answer set atoms as printed by the solver
+    Text,
+    /// The encoding written by --dump-bin
+    Bin,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ColorMode {
+    /// Each block in the color of its id
+    Id,
+    /// Each cell by its distance from the center of the box
+    Radial,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum PipeColors {
+    /// Every pipe in the same red
+    Uniform,
+    /// One color per pair of faces a segment joins
+    Pair,
+}
+
+// Primitive meshes come with smooth normals; flat shading splits every
+// triangle so each face gets its own normal.
+fn shaded(mesh: impl Into<Mesh>, config: &Config) -> Mesh {
+    let mesh = mesh.into();
+    if config.flat_shading {
+        mesh.with_duplicated_vertices().with_computed_flat_normals()
+    } else {
+        mesh
+    }
+}
+
+/// The cuboid mesh of `size`, added on first use and shared after that.
+fn cuboid_mesh(
+    cache: &mut HashMap<[u32; 3], Handle<Mesh>>,
+    meshes: &mut Assets<Mesh>,
+    size: Vec3,
+    config: &Config,
+) -> Handle<Mesh> {
+    cache
+        .entry(size.to_array().map(f32::to_bits))
+        .or_insert_with(|| meshes.add(shaded(Cuboid::from_size(size), config)))
+        .clone()
+}
+
+/// The cylinder mesh of `radius` and `length`, shared like `cuboid_mesh`.
+fn cylinder_mesh(
+    cache: &mut HashMap<[u32; 2], Handle<Mesh>>,
+    meshes: &mut Assets<Mesh>,
+    radius: f32,
+    length: f32,
+    config: &Config,
+) -> Handle<Mesh> {
+    cache
+        .entry([radius.to_bits(), length.to_bits()])
+        .or_insert_with(|| meshes.add(shaded(Cylinder::new(radius, length), config)))
+        .clone()
+}
+
+/// Lightness of each pass around the hue circle, far enough apart that
+/// two ids sharing a hue bucket still read as different blocks.
+const BUCKET_LIGHTNESS: [f32; 3] = [0.6, 0.42, 0.76];
+
+/// Color of block `id`, the same on every run: the id is hashed (FNV-1a,
+/// after the seed if any) into one of `--color-buckets` evenly spaced
+/// hues, and the rest of the hash picks the lightness, so ids past the
+/// number of buckets cycle through the hues again lighter or darker.
+fn id_color(id: &str, config: &Config) -> Color {
+    let seed_bytes = config.seed.map(u64::to_le_bytes);
+    let bytes = seed_bytes.iter().flatten().chain(id.as_bytes());
+    let hash = bytes.fold(0xcbf2_9ce4_8422_2325_u64, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    let buckets = config.color_buckets as u64;
+    let hue = (hash % buckets) as f32 * 360.0 / buckets as f32;
+    let cycle = (hash / buckets) as usize % BUCKET_LIGHTNESS.len();
+    Color::hsl(hue, 0.7, BUCKET_LIGHTNESS[cycle])
 }
 
+/// Material shared by every cell of a block, keyed by block id.
+#[derive(Resource)]
+struct BlockMaterials {
+    by_id: HashMap<String, Handle<StandardMaterial>>,
+    /// Darker variant for cells on even layers, with `--layer-stripe`.
+    striped: HashMap<String, Handle<StandardMaterial>>,
+    /// Colormapped material of every cell, overriding the block colors,
+    /// with `--scalar-field` or `--color-mode radial`.
+    by_cell: HashMap<(usize, usize, usize), Handle<StandardMaterial>>,
+    /// Neutral gray for an id without a material, until `recovery` gives
+    /// it one back.
+    fallback: Handle<StandardMaterial>,
+}
+
+impl BlockMaterials {
+    /// Material of cell `(x, y, z)` of block `id`.
+    fn cell(&self, id: &str, (x, y, z): (usize, usize, usize)) -> &Handle<StandardMaterial> {
+        if let Some(material) = self.by_cell.get(&(x, y, z)) {
+            material
+        } else if y.is_multiple_of(2)
+            && let Some(striped) = self.striped.get(id)
+        {
+            striped
+        } else {
+            self.by_id.get(id).unwrap_or(&self.fallback)
+        }
+    }
+}
+
+const FALLBACK_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+
 #[derive(Component)]
 struct Block {
+    x: usize,
+    y: usize,
+    z: usize,
+}
+
+/// One pipe segment, tagged with the cell it runs through.
+#[derive(Component)]
+struct Pipe {
+    x: usize,
     y: usize,
+    z: usize,
+    /// The piece runs to the side the segment comes in from. Joints and
+    /// mitered segments cover both sides and count as outlets.
+    is_inlet: bool,
+    /// Sides of the cell the piece reaches: one for a stub or span, both
+    /// of its segment's for a mitered one, none for a joint.
+    sides: [Option<Direction>; 2],
+}
+
+impl Pipe {
+    /// The piece is in layer `y` or runs into it from a neighboring one.
+    fn on_layer(&self, y: usize) -> bool {
+        self.y == y
+            || self.sides.iter().flatten().any(|side| match side {
+                Direction::Above => self.y + 1 == y,
+                Direction::Below => self.y == y + 1,
+                _ => false,
+            })
+    }
+}
+
+/// Two tiles a side, one per cell once repeated over the floor.
+fn floor_tiles() -> Image {
+    let (light, dark) = ([255; 4], [225, 225, 225, 255]);
+    let mut image = Image::new(
+        Extent3d {
+            width: 2,
+            height: 2,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        [light, dark, dark, light].concat(),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: ImageAddressMode::Repeat,
+        address_mode_v: ImageAddressMode::Repeat,
+        ..ImageSamplerDescriptor::nearest()
+    });
+    image
 }
 
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
     positions: Res<Positions>,
     config: Res<Config>,
+    clear_color: Res<ClearColor>,
 ) {
+    debug!(
+        "Solution has {} filled cells, {} pipe cells and {} empty cells",
+        positions.positions.len(),
+        positions.pipe_positions.len(),
+        positions.empty.len()
+    );
+    spawn_solution(&mut commands, &mut meshes, &mut materials, &positions, &config, None);
 
-    // let mat = materials.add(StandardMaterial {
-    //     base_color: Color::srgba(0.8, 0.7, 0.6, 0.5),
-    //     alpha_mode: AlphaMode::Add,
-    //     ..default()
-    // });
+    if config.floor {
+        let center = camera::box_center(&config);
+
+        // Just below the bottom faces of the first layer, a shade lighter
+        // than the background so it reads as a surface, and one cell wider
+        // than the footprint on every side so the shadow lands on it. The
+        // tiles line up with the cells above them.
+        let margin = 2.0;
+        let size = Vec2::new(config.width as f32 + margin, config.depth as f32 + margin);
+        commands.spawn((
+            Mesh3d(meshes.add(Plane3d::default().mesh().size(size.x, size.y))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: clear_color.0.lighter(0.06),
+                base_color_texture: Some(images.add(floor_tiles())),
+                uv_transform: Affine2::from_scale(size / 2.0),
+                perceptual_roughness: 1.0,
+                ..default()
+            })),
+            Transform::from_xyz(center.x, config.origin.y + 0.49, center.z),
+        ));
+
+        // Ambient light casts no shadows, so the floor brings its own light.
+        commands.spawn((
+            DirectionalLight {
+                shadows_enabled: true,
+                ..default()
+            },
+            Transform::from_xyz(center.x - 2.0, center.y + 4.0, center.z + 1.0).looking_at(center, Vec3::Y),
+        ));
+    }
 
-    let mut rng = rand::rng();
-    let mut materials_block = HashMap::new();
-    for block in positions.positions.values() {
+    commands.insert_resource(AmbientLight {
+        color: Color::WHITE,
+        brightness: 500.0, // You can tweak this for softer/harsher ambient light
+    });
+}
 
-        let r: u8 = rng.random_range(0..=255);
-        let g: u8 = rng.random_range(0..=255);
-        let b: u8 = rng.random_range(0..=255);
+/// Spawns the blocks and pipes of `positions` and inserts their
+/// `BlockMaterials`, reusing the colors of `previous` for known ids.
+fn spawn_solution(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    positions: &Positions,
+    config: &Config,
+    previous: Option<&BlockMaterials>,
+) {
+    let mut rng = config.seed.map_or_else(StdRng::from_os_rng, StdRng::seed_from_u64);
+    // Ids seen in an earlier state keep their color.
+    let mut block_materials = BlockMaterials {
+        by_id: previous.map_or_else(HashMap::new, |previous| previous.by_id.clone()),
+        striped: previous.map_or_else(HashMap::new, |previous| previous.striped.clone()),
+        by_cell: HashMap::new(),
+        fallback: previous.map_or_else(
+            || materials.add(FALLBACK_COLOR),
+            |previous| previous.fallback.clone(),
+        ),
+    };
+    for BlockInfo { id: block, .. } in positions.positions.values() {
+        if block_materials.by_id.contains_key(block) {
+            continue;
+        }
 
-        let random_color = Color::srgb_u8(r, g, b);
+        let block_color = id_color(block, config);
         let mat = materials.add(StandardMaterial {
-            base_color: random_color,
-            // alpha_mode: AlphaMode::Add,
+            base_color: block_color,
             ..default()
         });
-        materials_block.insert(block, mat);
+        block_materials.by_id.insert(block.clone(), mat);
+
+        if let Some(strength) = config.layer_stripe {
+            let striped = materials.add(StandardMaterial {
+                base_color: block_color.darker(strength),
+                ..default()
+            });
+            block_materials.striped.insert(block.clone(), striped);
+        }
+    }
+
+    // Radial distances follow the box, which can be resized at runtime.
+    let radial = match config.color_mode {
+        ColorMode::Id => None,
+        ColorMode::Radial => Some(radial::distance_field(config, config.distance_metric)),
+    };
+    if let Some(field) = radial.as_ref().or(config.scalar_field.as_ref()) {
+        let neutral = materials.add(StandardMaterial {
+            base_color: scalar_field::NEUTRAL_COLOR,
+            ..default()
+        });
+        let steps: Vec<_> = (0..scalar_field::STEPS)
+            .map(|step| {
+                materials.add(StandardMaterial {
+                    base_color: scalar_field::step_color(step),
+                    ..default()
+                })
+            })
+            .collect();
+        for &cell in positions.positions.keys() {
+            let material = field.step(cell).map_or(&neutral, |step| &steps[step]);
+            block_materials.by_cell.insert(cell, material.clone());
+        }
     }
 
     // commands.spawn((
@@ -144,74 +737,246 @@ fn setup(
         ..default()
     });
 
+    let pair_materials: HashMap<_, _> = match config.pipe_colors {
+        PipeColors::Uniform => HashMap::new(),
+        PipeColors::Pair => pipe_pairs::shape_colors(positions)
+            .into_iter()
+            .map(|(shape, (base_color, _))| {
+                (shape, materials.add(StandardMaterial { base_color, ..default() }))
+            })
+            .collect(),
+    };
+
+    let mut instances = Vec::new();
+    // Both cells of a span list the connection; draw it once, keyed by its
+    // midpoint in half-cell units.
+    let mut spans = HashSet::new();
+    // Mitered meshes depend only on the pair of directions.
+    let mut segment_meshes: HashMap<(Direction, Direction), Handle<Mesh>> = HashMap::new();
+    // Every cell is the same cube, and pipe pieces come in a handful of
+    // sizes, so share their meshes rather than adding one per entity.
+    let cube = meshes.add(shaded(Cuboid::new(1.0, 1.0, 1.0), config));
+    let mut pipe_meshes: HashMap<[u32; 3], Handle<Mesh>> = HashMap::new();
+    let mut cylinder_meshes: HashMap<[u32; 2], Handle<Mesh>> = HashMap::new();
+    let joint = meshes.add(shaded(Sphere::new(config.joint_radius()), config));
+
     for x in 1..=config.width {
         for z in 1..=config.depth {
             for y in 1..=config.height {
-                let block = positions
-                    .positions
+                if config.face.is_some_and(|face| !face.on_face(x, y, z, config)) {
+                    continue;
+                }
+
+                // Cells are missing while a watched file is still being written.
+                let Some(block) = positions.positions.get(&(x, y, z)) else {
+                    continue;
+                };
+
+                let mat = block_materials.cell(&block.id, (x, y, z));
+
+                let mut transform = Transform::from_translation(config.cell_position((x, y, z)))
+                    .with_rotation(block.orientation.rotation());
+                if let Some(degrees) = config.jitter_rotation {
+                    let max = degrees.to_radians();
+                    let mut angle = || rng.random_range(-max..=max);
+                    transform.rotation = Quat::from_euler(EulerRot::XYZ, angle(), angle(), angle()) * transform.rotation;
+                }
+
+                if config.instanced {
+                    let color = materials.get(mat).map_or(Color::WHITE, |mat| mat.base_color);
+                    // Instances aren't rotated, by the orientation or the
+                    // jitter.
+                    instances.push(BlockInstance {
+                        position: transform.translation,
+                        // Drawn after the opaque pass, so keep the faces off
+                        // the flush ends of the pipes to avoid z-fighting.
+                        scale: 0.999,
+                        color: color.to_linear().to_f32_array(),
+                    });
+                } else {
+                    commands.spawn((
+                        Mesh3d(cube.clone()),
+                        MeshMaterial3d(mat.clone()),
+                        transform,
+                        Block { x, y, z },
+                    ));
+                }
+
+                // A sphere at the center closes the bend between the segments.
+                let segments = positions
+                    .pipe_positions
                     .get(&(x, y, z))
-                    .unwrap();
+                    .filter(|_| config.pipes);
+                if let Some(&(in_dir, out_dir)) = segments.and_then(|segments| segments.first())
+                    && !config.mitered_pipes()
+                {
+                    let pipe_mat = pair_materials
+                        .get(&pipe_pairs::shape(in_dir, out_dir))
+                        .unwrap_or(&pipe_mat);
+                    // As thick as the thickest piece meeting it.
+                    let thickness = segments
+                        .into_iter()
+                        .flatten()
+                        .flat_map(|&(in_dir, out_dir)| [in_dir, out_dir])
+                        .map(|dir| config.pipe_thickness((x, y, z), dir))
+                        .fold(1.0_f32, f32::max);
+                    commands.spawn((
+                        Mesh3d(joint.clone()),
+                        MeshMaterial3d(pipe_mat.clone()),
+                        Transform::from_translation(config.cell_position((x, y, z)))
+                            .with_scale(Vec3::splat(thickness)),
+                        Pipe { x, y, z, is_inlet: false, sides: [None, None] },
+                    ));
+                }
+
+                for (in_dir, out_dir) in segments.into_iter().flatten() {
+                    let pipe_mat = pair_materials
+                        .get(&pipe_pairs::shape(*in_dir, *out_dir))
+                        .unwrap_or(&pipe_mat);
 
-                let mat = materials_block
-                    .get(block)
-                    .unwrap();
+                    if config.pipe_style == PipeStyle::Span {
+                        let center = config.cell_position((x, y, z));
+                        for dir in [in_dir, out_dir] {
+                            let midpoint = (center * 2.0 + dir.offset()).as_ivec3();
+                            if !spans.insert(midpoint) {
+                                continue;
+                            }
 
-                commands.spawn((
-                    Mesh3d(meshes.add(Cuboid::new(1.0, 1.0, 1.0))),
-                    MeshMaterial3d(mat.clone()),
-                    Transform::from_xyz(x as f32, y as f32, z as f32),
-                    Block { y },
-                ));
+                            let thickness = config
+                                .pipe_flow
+                                .as_ref()
+                                .map_or(1.0, |flow| flow.span_thickness((x, y, z), *dir));
+                            let transform = Transform::from_translation(center + dir.offset() * 0.5);
+                            let (mesh, transform) = match config.pipe_shape {
+                                PipeShape::Cuboid => {
+                                    let size = Vec3::splat(0.3) + dir.offset().abs();
+                                    (
+                                        cuboid_mesh(&mut pipe_meshes, meshes, size, config),
+                                        transform.with_scale(pipe_flow::across(*dir, thickness)),
+                                    )
+                                }
+                                PipeShape::Cylinder => (
+                                    cylinder_mesh(&mut cylinder_meshes, meshes, config.pipe_radius, 1.3, config),
+                                    transform
+                                        .with_rotation(dir.pipe_rotation())
+                                        .with_scale(Vec3::new(thickness, 1.0, thickness)),
+                                ),
+                            };
+                            commands.spawn((
+                                Mesh3d(mesh),
+                                MeshMaterial3d(pipe_mat.clone()),
+                                transform,
+                                Pipe { x, y, z, is_inlet: dir == in_dir, sides: [Some(*dir), None] },
+                            ));
+                        }
+                        continue;
+                    }
 
-                if let Some((in_dir, out_dir)) = positions.pipe_positions.get(&(x, y, z)) {
+                    if config.pipe_shape == PipeShape::Cylinder {
+                        // From the center to the face; the joint sphere
+                        // rounds off the bend in between.
+                        for dir in [in_dir, out_dir] {
+                            let center = config.cell_position((x, y, z));
+                            let thickness = config.pipe_thickness((x, y, z), *dir);
+                            commands.spawn((
+                                Mesh3d(cylinder_mesh(&mut cylinder_meshes, meshes, config.pipe_radius, 0.5, config)),
+                                MeshMaterial3d(pipe_mat.clone()),
+                                Transform::from_translation(center + dir.offset() * 0.25)
+                                    .with_rotation(dir.pipe_rotation())
+                                    .with_scale(Vec3::new(thickness, 1.0, thickness)),
+                                Pipe { x, y, z, is_inlet: dir == in_dir, sides: [Some(*dir), None] },
+                            ));
+                        }
+                        continue;
+                    }
 
-                    let width = in_dir.pipe_width();
-                    let height = in_dir.pipe_height();
-                    let depth = in_dir.pipe_depth();
+                    if config.pipe_quality == PipeQuality::High {
+                        let mesh = segment_meshes
+                            .entry((*in_dir, *out_dir))
+                            .or_insert_with(|| meshes.add(pipe_mesh::segment_mesh(*in_dir, *out_dir)));
+                        commands.spawn((
+                            Mesh3d(mesh.clone()),
+                            MeshMaterial3d(pipe_mat.clone()),
+                            Transform::from_translation(config.cell_position((x, y, z))),
+                            Pipe { x, y, z, is_inlet: false, sides: [Some(*in_dir), Some(*out_dir)] },
+                        ));
+                        continue;
+                    }
 
-                    let in_pipe_transform = in_dir.pipe_transform(x as f32, y as f32, z as f32);
+                    let in_size = Vec3::new(in_dir.pipe_width(), in_dir.pipe_height(), in_dir.pipe_depth());
+
+                    let mut in_pipe_transform = in_dir.pipe_transform(x as f32, y as f32, z as f32);
+                    in_pipe_transform.translation += config.origin;
+                    in_pipe_transform.scale = pipe_flow::across(*in_dir, config.pipe_thickness((x, y, z), *in_dir));
 
                     commands.spawn((
-                        Mesh3d(meshes.add(Cuboid::new(width, height, depth))),
+                        Mesh3d(cuboid_mesh(&mut pipe_meshes, meshes, in_size, config)),
                         MeshMaterial3d(pipe_mat.clone()),
                         in_pipe_transform,
+                        Pipe { x, y, z, is_inlet: true, sides: [Some(*in_dir), None] },
                     ));
 
-                    let width = out_dir.pipe_width();
-                    let height = out_dir.pipe_height();
-                    let depth = out_dir.pipe_depth();
-
-                    let out_pipe_transform = out_dir.pipe_transform(x as f32, y as f32, z as f32);
+                    let out_size = Vec3::new(out_dir.pipe_width(), out_dir.pipe_height(), out_dir.pipe_depth());
 
+                    let mut out_pipe_transform = out_dir.pipe_transform(x as f32, y as f32, z as f32);
+                    out_pipe_transform.translation += config.origin;
+                    out_pipe_transform.scale = pipe_flow::across(*out_dir, config.pipe_thickness((x, y, z), *out_dir));
 
-                    dbg!(x, y, z, in_dir, out_dir, in_pipe_transform, out_pipe_transform);
+                    debug!(
+                        "Pipe at ({x},{y},{z}) from {in_dir:?} to {out_dir:?}: {:?}, {:?}",
+                        in_pipe_transform.translation, out_pipe_transform.translation
+                    );
 
                     commands.spawn((
-                        Mesh3d(meshes.add(Cuboid::new(width, height, depth))),
+                        Mesh3d(cuboid_mesh(&mut pipe_meshes, meshes, out_size, config)),
                         MeshMaterial3d(pipe_mat.clone()),
                         out_pipe_transform,
+                        Pipe { x, y, z, is_inlet: false, sides: [Some(*out_dir), None] },
                     ));
                 }
             }
         }
     }
 
-    commands.insert_resource(AmbientLight {
-        color: Color::WHITE,
-        brightness: 500.0, // You can tweak this for softer/harsher ambient light
-    });
-    //
-    // Camera
+    if config.instanced {
+        commands.spawn(instancing::instanced_blocks(cube.clone(), instances));
+    }
+
+    commands.insert_resource(block_materials);
+}
+
+/// Everything `spawn_solution` spawns.
+type SolutionEntities = Or<(With<Block>, With<Pipe>, With<InstancedBlocks>)>;
+
+/// Replaces the solution on screen by `positions`, keeping block colors.
+fn respawn_solution(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    spawned: &Query<Entity, SolutionEntities>,
+    positions: Positions,
+    config: &Config,
+    block_materials: &BlockMaterials,
+) {
+    for entity in spawned.iter() {
+        commands.entity(entity).despawn();
+    }
+    spawn_solution(commands, meshes, materials, &positions, config, Some(block_materials));
+    commands.insert_resource(positions);
+}
+
+fn spawn_camera(mut commands: Commands, config: Res<Config>, adapter: Res<RenderAdapter>) {
     commands.spawn((
         // Note we're setting the initial position below with yaw, pitch, and radius, hence
         // we don't set transform on the camera.
         PanOrbitCamera {
             // Set focal point (what the camera should look at)
-            focus: Vec3::new(2.5, 2.5, 2.5),
+            focus: camera::box_center(&config),
             // Set the starting position, relative to focus (overrides camera's transform).
+            // Far enough out that the whole box fits, whatever its size.
             yaw: Some(TAU / 8.0),
             pitch: Some(TAU / 8.0),
-            radius: Some(5.0),
+            radius: Some(camera::framing_distance(&config, config.fov)),
             // Set limits on rotation and zoom
             // yaw_upper_limit: Some(TAU / 4.0),
             // yaw_lower_limit: Some(-TAU / 4.0),
@@ -235,16 +1000,22 @@ fn setup(
             touch_controls: TouchControls::TwoFingerOrbit,
             ..default()
         },
+        camera::projection(&config),
+        camera::supported_msaa(config.msaa, &adapter),
+        // The picture-in-picture camera renders later and would take the
+        // HUD otherwise.
+        IsDefaultUiCamera,
     ));
 }
 
 // This is how you can change config at runtime.
-// Press 'T' to toggle the camera controls.
+// Press 'T' (or the `toggle_camera_controls` binding) to toggle the camera controls.
 fn toggle_camera_controls_system(
     key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
     mut pan_orbit_query: Query<&mut PanOrbitCamera>,
 ) {
-    if key_input.just_pressed(KeyCode::KeyT) {
+    if keymap.just_pressed(Action::CameraControls, &key_input) {
         for mut pan_orbit in pan_orbit_query.iter_mut() {
             pan_orbit.enabled = !pan_orbit.enabled;
         }
@@ -253,123 +1024,1679 @@ fn toggle_camera_controls_system(
 
 fn switch_layer_system(
     key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
     mut config: ResMut<Config>,
     mut active_layer: ResMut<ActiveLayer>,
-    mut query: Query<(&Block, &mut Visibility)>,
+    mut query: Query<(&Block, &mut Visibility), Without<Pipe>>,
+    mut pipes: Query<(Ref<Pipe>, &mut Visibility), Without<Block>>,
 ) {
-    // if key_input.just_pressed(KeyCode::ArrowUp) {
-    //     active_layer.y += 1;
-    // } else if key_input.just_pressed(KeyCode::ArrowDown) {
-    //     if active_layer.y > 0 {
-    //         active_layer.y -= 1;
-    //     }
-    // }
-    //
-    if key_input.just_pressed(KeyCode::KeyH) {
+    // Moving the layer slices the box if it isn't sliced yet.
+    if keymap.just_pressed(Action::LayerUp, &key_input) {
+        active_layer.y = (active_layer.y + 1).min(config.height);
+        active_layer.sliced = true;
+    } else if keymap.just_pressed(Action::LayerDown, &key_input) {
+        active_layer.y = active_layer.y.saturating_sub(1).max(1);
+        active_layer.sliced = true;
+    }
+    if keymap.just_pressed(Action::LayerSlice, &key_input) {
+        active_layer.sliced = !active_layer.sliced;
+    }
+    if keymap.just_pressed(Action::Hide, &key_input) {
         config.hide = !config.hide;
     }
+    // Only touched when toggled, sliced or respawned, so the drop
+    // animation can hold back the pipes of falling blocks. Pipes entering
+    // the slice from above or below stay, to show where it's fed from.
+    let pipes_toggled = keymap.just_pressed(Action::HidePipes, &key_input);
+    if pipes_toggled {
+        config.hide_pipes = !config.hide_pipes;
+    }
+    let refresh = pipes_toggled || active_layer.is_changed();
+    for (pipe, mut visibility) in pipes.iter_mut() {
+        if refresh || pipe.is_added() {
+            *visibility = if config.hide_pipes || (active_layer.sliced && !pipe.on_layer(active_layer.y)) {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            };
+        }
+    }
 
     for (block, mut visibility) in query.iter_mut() {
-        *visibility = if config.hide {
+        *visibility = if config.hide || (active_layer.sliced && block.y != active_layer.y) {
             Visibility::Hidden
-
         } else {
             Visibility::Visible
         };
     }
 }
 
-fn parse_sol(line: &str) -> Positions {
-    let atoms: Vec<&str> = line.trim().split(" ").collect();
-    let mut positions: HashMap<(usize, usize, usize), String> = HashMap::new();
-    let mut pipe_positions: HashMap<(usize, usize, usize), (Direction, Direction)> = HashMap::new();
+/// What went wrong in one atom of the solver output. `index` counts the
+/// atoms of the model from 1, to find the atom in a long line.
+#[derive(Debug)]
+enum ParseSolError {
+    /// An atom without its closing parenthesis, usually a truncated line.
+    Malformed { index: usize, atom: String },
+    MissingField {
+        index: usize,
+        atom: String,
+        field: &'static str,
+    },
+    /// A coordinate that isn't a non-negative integer fitting in `usize`.
+    InvalidNumber {
+        index: usize,
+        atom: String,
+        field: &'static str,
+        token: String,
+        source: ParseIntError,
+    },
+    UnknownDirection {
+        index: usize,
+        atom: String,
+        field: &'static str,
+        token: String,
+    },
+    /// An axis other than `x`, `y` or `z`, or an angle that isn't a
+    /// multiple of 90 degrees below a full turn.
+    UnknownRotation {
+        index: usize,
+        atom: String,
+        field: &'static str,
+        token: String,
+    },
+    /// A second block placed in a cell that already holds another one.
+    Overlap {
+        index: usize,
+        atom: String,
+        cell: (usize, usize, usize),
+        other: String,
+    },
+}
 
-    dbg!(atoms.len());
+impl fmt::Display for ParseSolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseSolError::Malformed { index, atom } => {
+                write!(f, "atom {index} `{atom}`: expected `name(field,...)`")
+            }
+            ParseSolError::MissingField { index, atom, field } => {
+                write!(f, "atom {index} `{atom}`: missing {field}")
+            }
+            ParseSolError::InvalidNumber {
+                index,
+                atom,
+                field,
+                token,
+                source,
+            } => {
+                write!(f, "atom {index} `{atom}`: invalid {field} coordinate `{token}`: {source}")
+            }
+            ParseSolError::UnknownDirection {
+                index,
+                atom,
+                field,
+                token,
+            } => {
+                write!(f, "atom {index} `{atom}`: unknown {field} direction `{token}`")
+            }
+            ParseSolError::UnknownRotation {
+                index,
+                atom,
+                field,
+                token,
+            } => {
+                write!(f, "atom {index} `{atom}`: unknown rotation {field} `{token}`")
+            }
+            ParseSolError::Overlap {
+                index,
+                atom,
+                cell: (x, y, z),
+                other,
+            } => {
+                write!(f, "atom {index} `{atom}`: cell ({x},{y},{z}) already holds block {other}")
+            }
+        }
+    }
+}
 
-    for atom in &atoms {
-        match atom {
-            atom if atom.starts_with("block_pos(") => {
-                let mut atom = atom.strip_prefix("block_pos(").and_then(|s| s.strip_suffix(")")).and_then(|s| Some(s.split(","))).expect("Invalid atom");
-                let x = atom.next().unwrap().parse::<usize>().unwrap();
-                let y = atom.next().unwrap().parse::<usize>().unwrap();
-                let z = atom.next().unwrap().parse::<usize>().unwrap();
-                let block = atom.next().unwrap();
-                positions.insert((x, y, z), block.to_string());
-            },
-            atom if atom.starts_with("pipe_pos(") => {
-                dbg!(atom);
-                let mut atom = atom.strip_prefix("pipe_pos(").and_then(|s| s.strip_suffix(")")).and_then(|s| Some(s.split(","))).expect("Invalid atom");
-                let x = atom.next().unwrap().parse::<usize>().unwrap();
-                let y = atom.next().unwrap().parse::<usize>().unwrap();
-                let z = atom.next().unwrap().parse::<usize>().unwrap();
-                let in_dir = atom.next().unwrap().parse::<Direction>().unwrap();
-                let out_dir = atom.next().unwrap().parse::<Direction>().unwrap();
-                pipe_positions.insert((x, y, z), (in_dir, out_dir));
-            },
-            _ => (),
+impl std::error::Error for ParseSolError {}
+
+/// A solution that doesn't match the box it is shown in, which usually
+/// means the wrong `--width`, `--height` or `--depth`.
+#[derive(Debug)]
+enum BoundsError {
+    /// A block, pipe or empty cell outside the box.
+    Outside {
+        what: &'static str,
+        cell: (usize, usize, usize),
+        dimensions: (usize, usize, usize),
+    },
+    /// A cell of the box with no block that isn't marked empty either.
+    Unfilled { cell: (usize, usize, usize) },
+}
+
+impl fmt::Display for BoundsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoundsError::Outside {
+                what,
+                cell: (x, y, z),
+                dimensions: (width, height, depth),
+            } => {
+                write!(f, "{what} at ({x},{y},{z}) is outside the {width}x{height}x{depth} box")
+            }
+            BoundsError::Unfilled { cell: (x, y, z) } => {
+                write!(f, "cell ({x},{y},{z}) has no block and isn't marked empty")
+            }
         }
     }
+}
+
+impl std::error::Error for BoundsError {}
 
+/// The blocks, pipes and empty cells of `positions` in layer `y`.
+fn layer_slice(positions: &Positions, y: usize) -> Positions {
+    let in_layer = |&(_, cell_y, _): &(usize, usize, usize)| cell_y == y;
     Positions {
-        positions,
-        pipe_positions,
+        positions: positions
+            .positions
+            .iter()
+            .filter(|(cell, _)| in_layer(cell))
+            .map(|(&cell, block)| (cell, block.clone()))
+            .collect(),
+        pipe_positions: positions
+            .pipe_positions
+            .iter()
+            .filter(|(cell, _)| in_layer(cell))
+            .map(|(&cell, segments)| (cell, segments.clone()))
+            .collect(),
+        empty: positions.empty.iter().copied().filter(in_layer).collect(),
     }
 }
 
+/// Checks that `positions` fills exactly a box of `dimensions`, reporting
+/// the lowest offending cell.
+fn validate_bounds(positions: &Positions, dimensions: (usize, usize, usize)) -> Result<(), BoundsError> {
+    let (width, height, depth) = dimensions;
+    let inside = |&(x, y, z): &(usize, usize, usize)| {
+        (1..=width).contains(&x) && (1..=height).contains(&y) && (1..=depth).contains(&z)
+    };
 
-use clap::Parser;
+    let blocks = positions.positions.keys().map(|&cell| ("block", cell));
+    let pipes = positions.pipe_positions.keys().map(|&cell| ("pipe", cell));
+    let empty = positions.empty.iter().map(|&cell| ("empty cell", cell));
+    let outside = blocks
+        .chain(pipes)
+        .chain(empty)
+        .filter(|(_, cell)| !inside(cell))
+        .min_by_key(|&(_, cell)| cell);
+    if let Some((what, cell)) = outside {
+        return Err(BoundsError::Outside { what, cell, dimensions });
+    }
 
-/// Compute the volume of a box (defaults to a 3×3×3 cube)
-#[derive(Parser, Debug)]
-#[command(author, version, about)]
-struct Args {
-    /// Box height (default 3)
-    #[arg(long, default_value_t = 3)]
-    height: usize,
+    for x in 1..=width {
+        for y in 1..=height {
+            for z in 1..=depth {
+                let cell = (x, y, z);
+                if !positions.positions.contains_key(&cell) && !positions.empty.contains(&cell) {
+                    return Err(BoundsError::Unfilled { cell });
+                }
+            }
+        }
+    }
+    Ok(())
+}
 
-    /// Box width  (default 3)
-    #[arg(long, default_value_t = 3)]
-    width: usize,
+/// The fields of one atom, taken in order.
+struct AtomFields<'a> {
+    index: usize,
+    atom: &'a str,
+    fields: Box<dyn Iterator<Item = &'a str> + 'a>,
+}
 
-    /// Box depth  (default 3)
-    #[arg(long, default_value_t = 3)]
-    depth: usize,
+/// Splits `s` at the characters matching `separator` outside parentheses
+/// and quoted strings, so compound terms like `piece(a,2)` and strings
+/// like `"red corner"` stay whole.
+fn split_terms(s: &str, separator: fn(char) -> bool) -> impl Iterator<Item = &str> {
+    let mut depth = 0usize;
+    let mut quoted = false;
+    let mut escaped = false;
+    s.split(move |c: char| {
+        if quoted {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => quoted = false,
+                _ => (),
+            }
+            return false;
+        }
+        match c {
+            '"' => quoted = true,
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if depth == 0 => return separator(c),
+            _ => (),
+        }
+        false
+    })
 }
 
+impl<'a> AtomFields<'a> {
+    fn new(index: usize, atom: &'a str, name: &str) -> Result<AtomFields<'a>, ParseSolError> {
+        let fields = atom
+            .strip_prefix(name)
+            .and_then(|s| s.strip_prefix('('))
+            .and_then(|s| s.strip_suffix(')'))
+            .ok_or_else(|| ParseSolError::Malformed {
+                index,
+                atom: atom.to_string(),
+            })?;
+        Ok(AtomFields {
+            index,
+            atom,
+            fields: Box::new(split_terms(fields, |c| c == ',')),
+        })
+    }
 
-fn main() {
+    fn next(&mut self, field: &'static str) -> Result<&'a str, ParseSolError> {
+        self.fields.next().ok_or_else(|| ParseSolError::MissingField {
+            index: self.index,
+            atom: self.atom.to_string(),
+            field,
+        })
+    }
 
-    let args = Args::parse();
+    fn number<T: FromStr<Err = ParseIntError>>(&mut self, field: &'static str) -> Result<T, ParseSolError> {
+        let token = self.next(field)?;
+        token.parse::<T>().map_err(|source| ParseSolError::InvalidNumber {
+            index: self.index,
+            atom: self.atom.to_string(),
+            field,
+            token: token.to_string(),
+            source,
+        })
+    }
 
-    let config = Config {
-        width: args.width,
-        height: args.height,
-        depth: args.depth,
-        hide: false,
-    };
+    fn direction(&mut self, field: &'static str) -> Result<Direction, ParseSolError> {
+        let token = self.next(field)?;
+        token.parse::<Direction>().map_err(|()| ParseSolError::UnknownDirection {
+            index: self.index,
+            atom: self.atom.to_string(),
+            field,
+            token: token.to_string(),
+        })
+    }
 
-    let python = "../programs/env/bin/python";
+    fn rotation(&mut self, field: &'static str, tokens: &[&str]) -> Result<u8, ParseSolError> {
+        let token = self.next(field)?;
+        tokens
+            .iter()
+            .position(|&known| known == token)
+            .map(|position| position as u8)
+            .ok_or_else(|| ParseSolError::UnknownRotation {
+                index: self.index,
+                atom: self.atom.to_string(),
+                field,
+                token: token.to_string(),
+            })
+    }
 
-    let output = Command::new(python)
-        .arg("../programs/generator.py")
-        .arg(format!("--height={}", config.height))
-        .arg(format!("--width={}", config.width))
-        .arg(format!("--depth={}", config.depth))
-        .output()
-        .expect("Failed to execute python");
+    fn cell(&mut self) -> Result<(usize, usize, usize), ParseSolError> {
+        Ok((self.number("x")?, self.number("y")?, self.number("z")?))
+    }
 
-    let positions = parse_sol(String::from_utf8_lossy(&output.stdout).as_ref());
+    /// A block id: any term, numeric or symbolic, with the quotes of a
+    /// string dropped.
+    fn id(&mut self, field: &'static str) -> Result<&'a str, ParseSolError> {
+        let token = self.next(field)?;
+        Ok(token
+            .strip_prefix('"')
+            .and_then(|s| s.strip_suffix('"'))
+            .unwrap_or(token))
+    }
+}
 
-    // let positions = parse_sol("block_pos(2,1,1,1,1) block_pos(1,2,1,4,1) block_pos(1,1,2,7,1) block_pos(3,1,2,6,1) block_pos(3,2,2,3,1) block_pos(2,1,3,8,1) block_pos(3,1,3,2,1) block_pos(3,3,3,5,1) block_pos(1,1,3,8,2) block_pos(2,1,2,8,4) block_pos(1,2,2,7,2) block_pos(3,1,1,6,4) block_pos(2,3,3,5,4) block_pos(1,3,1,4,2) block_pos(3,3,2,3,3) block_pos(3,2,3,2,2) block_pos(1,1,1,1,2) block_pos(3,3,1,3,2) block_pos(2,3,1,4,4) block_pos(1,3,2,4,3) block_pos(2,3,2,5,2) block_pos(1,3,3,5,3) block_pos(3,2,1,6,3) block_pos(1,2,3,7,4) block_pos(2,2,2,8,3) block_pos(2,2,3,7,3) block_pos(2,2,1,6,2) pipe_pos(2,1,1,e,a) pipe_pos(1,2,1,e,a) pipe_pos(1,2,2,e,n) pipe_pos(1,2,3,e,s) pipe_pos(2,2,2,e,w) pipe_pos(2,2,3,e,w) pipe_pos(2,2,1,b,w) pipe_pos(1,3,1,b,w) pipe_pos(3,2,2,b,w) pipe_pos(3,3,3,b,a) pipe_pos(2,2,1,w,b) pipe_pos(1,3,1,w,b) pipe_pos(3,2,2,w,b) pipe_pos(3,2,3,w,a) pipe_pos(3,1,1,w,n) pipe_pos(2,2,2,w,e) pipe_pos(2,2,3,w,e) pipe_pos(2,1,1,a,e) pipe_pos(1,2,1,a,e) pipe_pos(3,2,3,a,w) pipe_pos(3,1,2,a,s) pipe_pos(3,3,3,a,b) pipe_pos(1,2,2,n,e) pipe_pos(3,1,1,n,w) pipe_pos(3,1,2,s,a) pipe_pos(1,2,3,s,e)");
+fn parse_interval(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(secs) if secs.is_finite() && secs > 0.0 => Ok(secs),
+        _ => Err(format!("expected a positive number of seconds; got `{s}`")),
+    }
+}
 
-    App::new()
-        .insert_resource(positions)
-        .insert_resource(config)
-        .insert_resource(ActiveLayer { y: 1 }) // <-- starting layer
-        .add_plugins(DefaultPlugins)
-        .add_plugins(PanOrbitCameraPlugin)
-        .add_systems(Startup, setup)
-        .add_systems(Update, toggle_camera_controls_system)
-        .add_systems(Update, switch_layer_system) // <-- add this
-        .run();
+/// Narrower than this looks flat and zooms in too far to handle; wider
+/// bends the edges of the box out of shape.
+const MIN_FOV: f32 = 5.0;
+const MAX_FOV: f32 = 150.0;
+
+fn parse_intensity(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(intensity) if intensity.is_finite() && intensity >= 0.0 => Ok(intensity),
+        _ => Err(format!("expected a non-negative intensity; got `{s}`")),
+    }
+}
+
+fn parse_fov(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(fov) if (MIN_FOV..=MAX_FOV).contains(&fov) => Ok(fov),
+        _ => Err(format!("expected a field of view between {MIN_FOV} and {MAX_FOV} degrees; got `{s}`")),
+    }
+}
+
+fn parse_seconds(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(secs) if secs.is_finite() && secs >= 0.0 => Ok(secs),
+        _ => Err(format!("expected a non-negative number of seconds; got `{s}`")),
+    }
+}
+
+fn parse_strength(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(strength) if (0.0..=1.0).contains(&strength) => Ok(strength),
+        _ => Err(format!("expected a strength between 0 and 1; got `{s}`")),
+    }
+}
+
+fn parse_opacity(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(opacity) if (0.0..=1.0).contains(&opacity) => Ok(opacity),
+        _ => Err(format!("expected an opacity between 0 and 1; got `{s}`")),
+    }
+}
+
+fn parse_pipe_radius(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(radius) if radius > 0.0 && radius <= 0.5 => Ok(radius),
+        _ => Err(format!("expected a radius above 0 and at most 0.5; got `{s}`")),
+    }
+}
+
+fn parse_buckets(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(buckets) if buckets > 0 => Ok(buckets),
+        _ => Err(format!("expected a positive number of hues; got `{s}`")),
+    }
+}
+
+fn parse_degrees(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(degrees) if degrees.is_finite() && degrees >= 0.0 => Ok(degrees),
+        _ => Err(format!("expected a non-negative angle in degrees; got `{s}`")),
+    }
+}
+
+fn parse_speed(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(speed) if speed.is_finite() && speed > 0.0 => Ok(speed),
+        _ => Err(format!("expected a positive speed; got `{s}`")),
+    }
+}
+
+#[derive(Resource)]
+struct ExitTimer(Timer);
+
+fn exit_after_system(time: Res<Time>, mut timer: ResMut<ExitTimer>, mut exit: EventWriter<AppExit>) {
+    if timer.0.tick(time.delta()).just_finished() {
+        exit.send(AppExit::Success);
+    }
+}
+
+/// Solution quality reported by clingo next to the answer set, when the
+/// program optimizes.
+#[derive(Resource, Debug, Default)]
+struct SolutionMetadata {
+    cost: Option<String>,
+    optimal: Option<bool>,
+}
+
+/// The models of a solver run, one per `Answer:` line. Output without
+/// those lines is a single model.
+fn split_models(output: &str) -> Vec<&str> {
+    let starts: Vec<usize> = output
+        .match_indices("Answer:")
+        .map(|(start, _)| start)
+        .filter(|&start| start == 0 || output[..start].ends_with('\n'))
+        .collect();
+    if starts.is_empty() {
+        return vec![output];
+    }
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(index, &start)| &output[start..starts.get(index + 1).copied().unwrap_or(output.len())])
+        .collect()
+}
+
+fn parse_metadata(output: &str) -> SolutionMetadata {
+    let mut metadata = SolutionMetadata::default();
+
+    for line in output.lines().map(str::trim) {
+        if let Some(cost) = line.strip_prefix("Optimization:") {
+            // Later models are better, keep the last cost.
+            metadata.cost = Some(cost.trim().to_string());
+        } else if line == "OPTIMUM FOUND" {
+            metadata.optimal = Some(true);
+        } else if let Some((key, value)) = line.split_once(':')
+            && key.trim() == "Optimum"
+        {
+            metadata.optimal = Some(value.trim() == "yes");
+        }
+    }
+
+    metadata
+}
+
+fn parse_sol(line: &str) -> Result<Positions, ParseSolError> {
+    let mut positions: HashMap<(usize, usize, usize), BlockInfo> = HashMap::new();
+    let mut pipe_positions: HashMap<(usize, usize, usize), Vec<(Direction, Direction)>> = HashMap::new();
+    let mut empty: HashSet<(usize, usize, usize)> = HashSet::new();
+    let mut orientations: HashMap<String, Orientation> = HashMap::new();
+
+    // Answer sets for big boxes can be megabytes on one line, so walk the
+    // atoms lazily instead of collecting them first.
+    // A truncated atom runs to the end of the line, newline included.
+    let atoms = split_terms(line, char::is_whitespace)
+        .map(str::trim)
+        .filter(|atom| !atom.is_empty());
+    for (i, atom) in atoms.enumerate() {
+        let index = i + 1;
+        match atom {
+            atom if atom.starts_with("block_pos(") => {
+                let mut fields = AtomFields::new(index, atom, "block_pos")?;
+                let cell = fields.cell()?;
+                let id = fields.id("block id")?;
+                let unit = fields.number("unit")?;
+                // The same block repeated is harmless, another one isn't.
+                if let Some(other) = positions.get(&cell)
+                    && other.id != id
+                {
+                    return Err(ParseSolError::Overlap {
+                        index,
+                        atom: atom.to_string(),
+                        cell,
+                        other: other.id.clone(),
+                    });
+                }
+                // The orientation comes from the block's `rot` atoms.
+                let block = BlockInfo {
+                    id: id.to_string(),
+                    unit,
+                    orientation: Orientation::default(),
+                };
+                positions.insert(cell, block);
+            },
+            atom if atom.starts_with("pipe_pos(") => {
+                let mut fields = AtomFields::new(index, atom, "pipe_pos")?;
+                let cell = fields.cell()?;
+                let in_dir = fields.direction("inlet")?;
+                let out_dir = fields.direction("outlet")?;
+                // The solver emits every segment in both orientations, keep one.
+                let segments = pipe_positions.entry(cell).or_default();
+                if !segments.contains(&(in_dir, out_dir)) && !segments.contains(&(out_dir, in_dir)) {
+                    segments.push((in_dir, out_dir));
+                }
+            },
+            atom if atom.starts_with("empty(") => {
+                let mut fields = AtomFields::new(index, atom, "empty")?;
+                empty.insert(fields.cell()?);
+            },
+            // `rot/1` only lists the angles.
+            atom if atom.starts_with("rot(") && atom.contains(',') => {
+                let mut fields = AtomFields::new(index, atom, "rot")?;
+                let axis = fields.rotation("axis", &["x", "y", "z"])?;
+                let id = fields.id("block id")?;
+                let turns = fields.rotation("angle", &["0", "90", "180", "270"])?;
+                orientations.entry(id.to_string()).or_default().0[axis as usize] = turns;
+            },
+            _ => (),
+        }
+    }
+
+    for block in positions.values_mut() {
+        block.orientation = orientations.get(&block.id).copied().unwrap_or_default();
+    }
+
+    Ok(Positions {
+        positions,
+        pipe_positions,
+        empty,
+    })
+}
+
+
+use clap::Parser;
+
+#[derive(clap::Subcommand, Debug)]
+enum Subcommand {
+    /// Print packing statistics of every solution in DIR, then exit
+    Stats {
+        dir: PathBuf,
+
+        /// Print the per-solution rows as CSV
+        #[arg(long)]
+        csv: bool,
+    },
+}
+
+/// Compute the volume of a box (defaults to a 3×3×3 cube)
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Subcommand>,
+
+    /// Box height (default 3)
+    #[arg(long, default_value_t = 3)]
+    height: usize,
+
+    /// Box width  (default 3)
+    #[arg(long, default_value_t = 3)]
+    width: usize,
+
+    /// Box depth  (default 3)
+    #[arg(long, default_value_t = 3)]
+    depth: usize,
+
+    /// Shift the whole box by X,Y,Z in world units
+    #[arg(long, value_name = "X,Y,Z", default_value = "0,0,0", value_parser = parse_origin)]
+    origin: Vec3,
+
+    /// Render FRAMES frames of a full orbit headlessly to numbered PNGs, then exit
+    #[arg(long, value_name = "FRAMES", value_parser = clap::value_parser!(u32).range(1..))]
+    turntable: Option<u32>,
+
+    /// Print the block and pipe segments at cell X,Y,Z, then exit
+    #[arg(long, value_name = "X,Y,Z", value_parser = parse_cell)]
+    query: Option<(usize, usize, usize)>,
+
+    /// Save a PNG legend of every block color headlessly to PATH, then exit
+    #[arg(long, value_name = "PATH", conflicts_with = "turntable")]
+    export_legend: Option<PathBuf>,
+
+    /// Save a PNG per step of a cutting plane swept through the box to DIR headlessly, then exit
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["turntable", "export_legend"])]
+    export_section_sweep: Option<PathBuf>,
+
+    /// Save a grayscale depth map of the box headlessly to PATH (near is black, far white), then exit
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["turntable", "export_legend", "export_section_sweep", "environment", "instanced"]
+    )]
+    export_depth: Option<PathBuf>,
+
+    /// Save a labeled orthographic PNG of each of the six faces of the box, all at the same scale, to DIR headlessly, then exit
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with_all = ["turntable", "export_legend", "export_section_sweep", "export_depth"]
+    )]
+    export_faces: Option<PathBuf>,
+
+    /// Axis the section sweep moves along
+    #[arg(long, value_enum, default_value_t = SectionAxis::Y)]
+    section_axis: SectionAxis,
+
+    /// Number of section sweep frames (default: one per layer along the axis)
+    #[arg(long, value_name = "STEPS", value_parser = clap::value_parser!(u32).range(1..))]
+    section_steps: Option<u32>,
+
+    /// Only show the outermost layer of blocks on one face of the box
+    #[arg(long, value_name = "top|bottom|north|south|east|west", value_parser = parse_face)]
+    face: Option<Direction>,
+
+    /// Only show the blocks and pipes of horizontal layer N, counted from 1 at the bottom (move with the arrows)
+    #[arg(long, value_name = "N")]
+    only_layer: Option<usize>,
+
+    /// Shade meshes with per-face normals instead of smooth ones
+    #[arg(long)]
+    flat_shading: bool,
+
+    /// Log what the renderer is doing, not just warnings and errors
+    #[arg(long, short)]
+    verbose: bool,
+
+    /// Load key bindings from a file of `action = KeyCode` lines
+    #[arg(long, value_name = "FILE")]
+    keymap: Option<PathBuf>,
+
+    /// Close the app after SECS seconds
+    #[arg(long, value_name = "SECS", value_parser = parse_seconds)]
+    exit_after: Option<f32>,
+
+    /// Multisample anti-aliasing sample count
+    #[arg(long, value_name = "off|2|4|8", default_value = "4", value_parser = parse_msaa)]
+    msaa: Msaa,
+
+    /// Average fall speed, in cells per second, of the drop-into-place animation
+    #[arg(long, value_name = "CELLS_PER_SEC", default_value_t = 6.0, value_parser = parse_speed)]
+    drop_speed: f32,
+
+    /// Show a ruler of one-unit segments along the box for scale
+    #[arg(long)]
+    scale_reference: bool,
+
+    /// Rest the box on a tiled floor plane that catches its shadow (the default)
+    #[arg(long, overrides_with = "no_floor")]
+    floor: bool,
+
+    /// Leave out the floor plane
+    #[arg(long)]
+    no_floor: bool,
+
+    /// Grid steps around the clicked block that focus mode (O) keeps visible
+    #[arg(long, value_name = "STEPS", default_value_t = 2)]
+    focus_radius: usize,
+
+    /// Start with the X, Y and Z axes drawn from the origin of the cell coordinates (toggle with E)
+    #[arg(long)]
+    axes: bool,
+
+    /// Hex colors of the X, Y and Z axes, for hues that are easier to tell apart than red, green and blue
+    #[arg(long, value_name = "X,Y,Z", default_value = "ff3333,33ff33,4d80ff", value_parser = parse_axis_colors)]
+    axis_colors: [Color; 3],
+
+    /// Start with the columns' footprint shaded on the floor (toggle with J)
+    #[arg(long)]
+    footprint: bool,
+
+    /// Start with the blocks translucent so the pipes show through them (toggle with M)
+    #[arg(long)]
+    transparent: bool,
+
+    /// Opacity each filled cell adds to its column's footprint
+    #[arg(long, default_value_t = 0.2, value_parser = parse_opacity)]
+    footprint_opacity: f32,
+
+    /// Tilt every cube randomly by up to DEGREES (default 3) so neighbours stand apart
+    #[arg(long, value_name = "DEGREES", num_args = 0..=1, default_missing_value = "3", value_parser = parse_degrees)]
+    jitter_rotation: Option<f32>,
+
+    /// Pick another block palette, and make the jitter the same on every run
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Number of evenly spaced hues block colors come from; fewer are easier to tell apart, more repeat less
+    #[arg(long, value_name = "N", default_value_t = 12, value_parser = parse_buckets)]
+    color_buckets: usize,
+
+    /// What block colors show
+    #[arg(long, value_enum, default_value_t = ColorMode::Id, conflicts_with = "scalar_field")]
+    color_mode: ColorMode,
+
+    /// How --color-mode radial measures the distance of a cell from the center of the box
+    #[arg(long, value_enum, default_value_t = DistanceMetric::Manhattan)]
+    distance_metric: DistanceMetric,
+
+    /// Compare against the intended shape in FILE, one `x,y,z` cell per line
+    #[arg(long, value_name = "FILE")]
+    target: Option<PathBuf>,
+
+    /// Start with the list of key bindings shown (toggle with F1)
+    #[arg(long)]
+    help_controls: bool,
+
+    /// Show the bounding box volume and exposed faces of the filled cells in the HUD
+    #[arg(long)]
+    shape_stats: bool,
+
+    /// Darken blocks on even layers by STRENGTH (default 0.15) to mark layer boundaries
+    #[arg(long, value_name = "STRENGTH", num_args = 0..=1, default_missing_value = "0.15", value_parser = parse_strength)]
+    layer_stripe: Option<f32>,
+
+    /// Print each block's id on the faces of its cubes
+    #[arg(long, conflicts_with = "instanced")]
+    id_textures: bool,
+
+    /// Draw all blocks in one instanced draw call, for very large boxes
+    #[arg(long)]
+    instanced: bool,
+
+    /// Don't spawn the pipes, to look at the block packing alone and start faster on large boxes
+    #[arg(long, conflicts_with_all = ["pipe_caps", "pipe_flow"])]
+    no_pipes: bool,
+
+    /// How pipe segments are colored
+    #[arg(long, value_enum, default_value_t = PipeColors::Uniform)]
+    pipe_colors: PipeColors,
+
+    /// Where pipe segments are drawn
+    #[arg(long, value_enum, default_value_t = PipeStyle::Stub)]
+    pipe_style: PipeStyle,
+
+    /// Cross-section of pipe segments; cylinders take the place of --pipe-quality high meshes
+    #[arg(long, value_enum, default_value_t = PipeShape::Cuboid)]
+    pipe_shape: PipeShape,
+
+    /// Radius of cylinder pipes, in cells
+    #[arg(long, value_name = "RADIUS", default_value_t = 0.15, value_parser = parse_pipe_radius)]
+    pipe_radius: f32,
+
+    /// Thicken the pipe pieces by the values in FILE (`x,y,z direction value` lines), fatter for larger values
+    #[arg(long, value_name = "FILE")]
+    pipe_flow: Option<PathBuf>,
+
+    /// Put a cap of STYLE where the pipes leave the box
+    #[arg(long, value_name = "STYLE", value_enum)]
+    pipe_caps: Option<CapStyle>,
+
+    /// Color of the pipe caps, as R,G,B,A between 0 and 1
+    #[arg(long, value_name = "R,G,B,A", default_value = "0.1,0.8,1,1", value_parser = parse_rgba)]
+    cap_color: Color,
+
+    /// Geometry of pipe segments drawn as stubs
+    #[arg(long, value_enum, default_value_t = PipeQuality::Low)]
+    pipe_quality: PipeQuality,
+
+    /// Start with the blocks drawn as faint wireframes around solid pipes (toggle with F)
+    #[arg(long)]
+    pipe_focus: bool,
+
+    /// Start with only the blocks the pipe passes through shown (toggle with K)
+    #[arg(long)]
+    pipe_tube: bool,
+
+    /// Outline the blocks the pipe tube hides
+    #[arg(long)]
+    tube_context: bool,
+
+    /// Color of the block wireframes in pipe focus and tube context, as R,G,B,A between 0 and 1
+    #[arg(long, value_name = "R,G,B,A", default_value = "1,1,1,0.15", value_parser = parse_rgba)]
+    wireframe_color: Color,
+
+    /// Color cells by the values in FILE (`x,y,z value` lines) instead of by block
+    #[arg(long, value_name = "FILE")]
+    scalar_field: Option<PathBuf>,
+
+    /// With several models in the output, cycle through them every SECONDS (step manually with S)
+    #[arg(long, value_name = "SECONDS", value_parser = parse_interval, conflicts_with = "watch")]
+    blink: Option<f32>,
+
+    /// Play the models in the output as an animation, one frame every SECONDS (pause with Space, scrub with comma and period)
+    #[arg(long, value_name = "SECONDS", value_parser = parse_interval, conflicts_with_all = ["watch", "blink"])]
+    playback: Option<f32>,
+
+    /// Vertical field of view of the camera in degrees; narrow for a flatter, almost orthographic look
+    #[arg(long, value_name = "DEGREES", default_value_t = 45.0, value_parser = parse_fov)]
+    fov: f32,
+
+    /// Settle the camera on yaw and pitch multiples of DEGREES after each orbit (toggle with A)
+    #[arg(long, value_name = "DEGREES", value_parser = snap::parse_snap_angle)]
+    snap_angle: Option<f32>,
+
+    /// Show a fixed overview of the whole box in a corner of the window (toggle with I)
+    #[arg(long)]
+    pip: bool,
+
+    /// Corner of the window the overview sits in
+    #[arg(long, value_enum, default_value_t = PipCorner::BottomRight)]
+    pip_corner: PipCorner,
+
+    /// Width and height of the overview as a fraction of the window's
+    #[arg(long, value_name = "FRACTION", default_value_t = 0.3, value_parser = pip::parse_pip_size)]
+    pip_size: f32,
+
+    /// Render a red/cyan stereo image for viewing with 3D glasses
+    #[arg(long)]
+    anaglyph: bool,
+
+    /// Distance between the two eyes of `--anaglyph` in cells; wider for stronger depth
+    #[arg(long, value_name = "DISTANCE", default_value_t = 0.25, value_parser = anaglyph::parse_eye_separation)]
+    eye_separation: f32,
+
+    /// Background around the box: `gradient`, or `skybox:PATH` to an image
+    #[arg(long, value_name = "gradient|skybox:PATH", value_parser = environment::parse_environment)]
+    environment: Option<Environment>,
+
+    /// Top color of the gradient environment, as R,G,B,A between 0 and 1
+    #[arg(long, value_name = "R,G,B,A", value_parser = parse_rgba)]
+    gradient_top: Option<Color>,
+
+    /// Bottom color of the gradient environment, as R,G,B,A between 0 and 1
+    #[arg(long, value_name = "R,G,B,A", value_parser = parse_rgba)]
+    gradient_bottom: Option<Color>,
+
+    /// Run the generator for each WxHxD size and show the boxes side by side, the first as the main one
+    #[arg(
+        long,
+        value_name = "WxHxD,...",
+        value_delimiter = ',',
+        value_parser = compare::parse_dimensions,
+        conflicts_with_all = ["solution_file", "stdin", "bundle", "width", "height", "depth", "watch"]
+    )]
+    compare_dims: Vec<(usize, usize, usize)>,
+
+    /// Resize the box a cell at a time and re-run the generator (pick the dimension with Tab, grow with Page Up, shrink with Page Down)
+    #[arg(long, conflicts_with_all = ["solution_file", "stdin", "bundle", "compare_dims", "blink", "playback"])]
+    grow: bool,
+
+    /// Read the solution (optionally gzipped) from stdin instead of running the generator
+    #[arg(long, conflicts_with_all = ["solution_file", "watch"])]
+    stdin: bool,
+
+    /// Load the box dimensions and solution from a JSON bundle FILE
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["solution_file", "stdin", "width", "height", "depth"])]
+    bundle: Option<PathBuf>,
+
+    /// Load the solution from FILE, written with --export-json, into the box given by --width, --height and --depth
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["solution_file", "stdin", "bundle", "watch", "compare_dims", "grow"])]
+    import_json: Option<PathBuf>,
+
+    /// Write the box dimensions and solution to a JSON bundle FILE, then exit
+    #[arg(long, value_name = "FILE")]
+    save_bundle: Option<PathBuf>,
+
+    /// Make the blocks of the cells listed in FILE (`x,y,z` lines) glow (step along the pipes with V)
+    #[arg(long, value_name = "FILE")]
+    active_cells: Option<PathBuf>,
+
+    /// Glow color of active cells, as R,G,B,A between 0 and 1
+    #[arg(long, value_name = "R,G,B,A", default_value = "1,0.8,0.2,1", value_parser = parse_rgba)]
+    active_color: Color,
+
+    /// Glow strength of active cells
+    #[arg(long, default_value_t = 4.0, value_parser = parse_intensity)]
+    active_intensity: f32,
+
+    /// Read the solution from FILE (optionally gzipped) instead of running the generator
+    #[arg(long, visible_alias = "input", value_name = "FILE")]
+    solution_file: Option<PathBuf>,
+
+    /// Re-render whenever the solution file changes, showing partial states as it is written
+    #[arg(long, requires = "solution_file")]
+    watch: bool,
+
+    /// Encoding of the solution file
+    #[arg(long, value_enum, default_value_t = SolutionFormat::Text, requires_if("bin", "solution_file"))]
+    format: SolutionFormat,
+
+    /// Write the solution in the binary format to FILE, then exit
+    #[arg(long, value_name = "FILE")]
+    dump_bin: Option<PathBuf>,
+
+    /// Write the pipe route to FILE, one cell and its directions per line from end to end, then exit
+    #[arg(long, value_name = "FILE")]
+    export_path: Option<PathBuf>,
+
+    /// Write a page viewing the solution in a browser to DIR, then exit
+    #[arg(long, value_name = "DIR")]
+    export_web: Option<PathBuf>,
+
+    /// Write the blocks and pipes to PATH as a Wavefront OBJ, with their colors in a .mtl beside it, then exit
+    #[arg(long, value_name = "PATH")]
+    export_obj: Option<PathBuf>,
+
+    /// Write the blocks, pipes and empty cells to PATH as JSON, without the box dimensions, then exit
+    #[arg(long, value_name = "PATH")]
+    export_json: Option<PathBuf>,
+
+    /// Write the camera, toggles, selection and model on screen to FILE when pressing F5
+    #[arg(long, value_name = "FILE")]
+    save_state: Option<PathBuf>,
+
+    /// Start from the state saved to FILE with --save-state
+    #[arg(long, value_name = "FILE")]
+    load_state: Option<PathBuf>,
+
+    /// Load and validate the solution, print a `key=value` summary and exit without rendering
+    #[arg(long, conflicts_with = "watch")]
+    headless: bool,
+}
+
+/// The `--headless` report, one `key=value` per line for scripts to grep.
+fn print_summary(config: &Config, positions: &Positions, metadata: &SolutionMetadata) {
+    let blocks: HashSet<&String> = positions.positions.values().map(|block| &block.id).collect();
+    let segments: usize = positions.pipe_positions.values().map(Vec::len).sum();
+    println!("dimensions={}x{}x{}", config.width, config.height, config.depth);
+    println!("blocks={}", blocks.len());
+    println!("filled_cells={}", positions.positions.len());
+    println!("empty_cells={}", positions.empty.len());
+    println!("pipe_cells={}", positions.pipe_positions.len());
+    println!("pipe_segments={segments}");
+    if let Some(cost) = &metadata.cost {
+        println!("cost={cost}");
+    }
+    if let Some(optimal) = metadata.optimal {
+        println!("optimal={optimal}");
+    }
+}
+
+fn parse_msaa(s: &str) -> Result<Msaa, String> {
+    match s {
+        "off" | "1" => Ok(Msaa::Off),
+        "2" => Ok(Msaa::Sample2),
+        "4" => Ok(Msaa::Sample4),
+        "8" => Ok(Msaa::Sample8),
+        _ => Err(format!("expected one of off, 2, 4, 8; got `{s}`")),
+    }
+}
+
+fn parse_cell(s: &str) -> Result<(usize, usize, usize), String> {
+    let coords: Vec<&str> = s.split(',').map(str::trim).collect();
+    match coords[..] {
+        [x, y, z] => match (x.parse(), y.parse(), z.parse()) {
+            (Ok(x), Ok(y), Ok(z)) => Ok((x, y, z)),
+            _ => Err(format!("expected three non-negative integers; got `{s}`")),
+        },
+        _ => Err(format!("expected X,Y,Z; got `{s}`")),
+    }
+}
+
+fn parse_rgba(s: &str) -> Result<Color, String> {
+    let channels: Vec<f32> = s
+        .split(',')
+        .map(|channel| channel.trim().parse::<f32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("expected R,G,B,A; got `{s}`"))?;
+    match channels[..] {
+        [r, g, b, a] if channels.iter().all(|channel| (0.0..=1.0).contains(channel)) => {
+            Ok(Color::srgba(r, g, b, a))
+        }
+        _ => Err(format!("expected four channels between 0 and 1; got `{s}`")),
+    }
+}
+
+fn parse_axis_colors(s: &str) -> Result<[Color; 3], String> {
+    let colors: Vec<Color> = s
+        .split(',')
+        .map(|color| Srgba::hex(color.trim()).map(Color::from))
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("expected three hex colors; got `{s}`"))?;
+    colors
+        .try_into()
+        .map_err(|_| format!("expected a color for each of X, Y and Z; got `{s}`"))
+}
+
+fn parse_origin(s: &str) -> Result<Vec3, String> {
+    let coords: Vec<f32> = s
+        .split(',')
+        .map(|coord| coord.trim().parse::<f32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("expected X,Y,Z; got `{s}`"))?;
+    match coords[..] {
+        [x, y, z] if coords.iter().all(|coord| coord.is_finite()) => Ok(Vec3::new(x, y, z)),
+        _ => Err(format!("expected three finite coordinates; got `{s}`")),
+    }
+}
+
+fn parse_face(s: &str) -> Result<Direction, String> {
+    match s {
+        "top" => Ok(Direction::Above),
+        "bottom" => Ok(Direction::Below),
+        "north" => Ok(Direction::North),
+        "south" => Ok(Direction::South),
+        "east" => Ok(Direction::East),
+        "west" => Ok(Direction::West),
+        _ => Err(format!("expected one of top, bottom, north, south, east, west; got `{s}`")),
+    }
+}
+
+
+// winit panics deep inside the event loop when there is no display, so
+// check up front. Only X11 and Wayland sessions can lack one.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn display_available() -> bool {
+    ["DISPLAY", "WAYLAND_DISPLAY"]
+        .iter()
+        .any(|var| std::env::var_os(var).is_some_and(|value| !value.is_empty()))
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn display_available() -> bool {
+    true
+}
+
+/// The solution text, from `--solution-file` or a fresh generator run.
+fn read_solution(args: &Args, config: &Config) -> String {
+    if args.stdin {
+        return input::read_solution_stdin().unwrap_or_else(|err| {
+            eprintln!("Failed to read the solution from stdin: {err}");
+            std::process::exit(1);
+        });
+    }
+
+    match &args.solution_file {
+        Some(path) => input::read_solution_file(path).unwrap_or_else(|err| {
+            eprintln!("Failed to read solution file {}: {err}", path.display());
+            std::process::exit(1);
+        }),
+        None => run_generator((config.width, config.height, config.depth)).unwrap_or_else(|err| {
+            eprintln!("Failed to run the generator: {err}");
+            std::process::exit(1);
+        }),
+    }
+}
+
+fn run_generator((width, height, depth): (usize, usize, usize)) -> std::io::Result<String> {
+    let python = "../programs/env/bin/python";
+
+    let output = Command::new(python)
+        .arg("../programs/generator.py")
+        .arg(format!("--height={height}"))
+        .arg(format!("--width={width}"))
+        .arg(format!("--depth={depth}"))
+        .output()?;
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn main() {
+
+    let args = Args::parse();
+
+    if let Some(Subcommand::Stats { dir, csv }) = &args.command {
+        if let Err(err) = stats::run(dir, (args.width, args.height, args.depth), *csv) {
+            eprintln!("Failed to read {}: {err}", dir.display());
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let bundle = args.bundle.as_ref().map(|path| {
+        Bundle::load(path).unwrap_or_else(|err| {
+            eprintln!("Failed to load bundle {}: {err}", path.display());
+            std::process::exit(1);
+        })
+    });
+
+    let bundle_positions = bundle.as_ref().map(|bundle| {
+        bundle.positions().unwrap_or_else(|err| {
+            eprintln!("Failed to load bundle {}: {err}", args.bundle.as_ref().unwrap().display());
+            std::process::exit(1);
+        })
+    });
+
+    let imported = args.import_json.as_ref().map(|path| {
+        SolutionJson::load(path).and_then(|solution| solution.positions()).unwrap_or_else(|err| {
+            eprintln!("Failed to import JSON {}: {err}", path.display());
+            std::process::exit(1);
+        })
+    });
+
+    // With --compare-dims the first size is the main box.
+    let (width, height, depth) = match (&bundle, args.compare_dims.first()) {
+        (Some(bundle), _) => (bundle.width, bundle.height, bundle.depth),
+        (None, Some(&dimensions)) => dimensions,
+        (None, None) => (args.width, args.height, args.depth),
+    };
+    // Without a snapshot every setting comes from the command line.
+    let state = args
+        .load_state
+        .as_ref()
+        .map(|path| {
+            let mut state = State::load(path).unwrap_or_else(|err| {
+                eprintln!("Failed to load state {}: {err}", path.display());
+                std::process::exit(1);
+            });
+            // Saved from a taller solution.
+            if let Some(layer) = state.layer
+                && !(1..=height).contains(&layer)
+            {
+                eprintln!("Ignoring layer {layer} of state {}: the box has {height} layers", path.display());
+                state.layer = None;
+            }
+            state
+        })
+        .unwrap_or_default();
+
+    let config = Config {
+        width,
+        height,
+        depth,
+        origin: args.origin,
+        hide: state.hide_blocks.unwrap_or(false),
+        hide_pipes: state.hide_pipes.unwrap_or(false),
+        pipes: !args.no_pipes,
+        face: args.face,
+        flat_shading: args.flat_shading,
+        msaa: args.msaa,
+        scale_reference: args.scale_reference,
+        floor: !args.no_floor,
+        jitter_rotation: args.jitter_rotation,
+        seed: args.seed,
+        color_buckets: args.color_buckets,
+        color_mode: state.color_mode.unwrap_or(args.color_mode),
+        distance_metric: state.distance_metric.unwrap_or(args.distance_metric),
+        help_controls: args.help_controls,
+        shape_stats: args.shape_stats,
+        instanced: args.instanced,
+        pipe_colors: args.pipe_colors,
+        pipe_style: args.pipe_style,
+        pipe_shape: args.pipe_shape,
+        pipe_radius: args.pipe_radius,
+        pipe_quality: args.pipe_quality,
+        pipe_flow: args.pipe_flow.as_ref().map(|path| {
+            PipeFlow::load(path).unwrap_or_else(|err| {
+                eprintln!("Failed to load pipe flow {}: {err}", path.display());
+                std::process::exit(1);
+            })
+        }),
+        pipe_focus: state.pipe_focus.unwrap_or(args.pipe_focus),
+        pipe_tube: state.pipe_tube.unwrap_or(args.pipe_tube),
+        tube_context: args.tube_context,
+        wireframe_color: args.wireframe_color,
+        axis_colors: args.axis_colors,
+        scalar_field: args.scalar_field.as_ref().map(|path| {
+            ScalarField::load(path).unwrap_or_else(|err| {
+                eprintln!("Failed to load scalar field {}: {err}", path.display());
+                std::process::exit(1);
+            })
+        }),
+        layer_stripe: args.layer_stripe,
+        fov: args.fov.to_radians(),
+    };
+
+    let keymap = match &args.keymap {
+        Some(path) => Keymap::load(path).unwrap_or_else(|err| {
+            eprintln!("Failed to load keymap {}: {err}", path.display());
+            std::process::exit(1);
+        }),
+        None => Keymap::default(),
+    };
+
+    let active_cells = match &args.active_cells {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                input::parse_cell_list(&contents).map_err(|line| format!("line {line}: expected `x,y,z`"))
+            })
+            .unwrap_or_else(|err| {
+                eprintln!("Failed to load active cells {}: {err}", path.display());
+                std::process::exit(1);
+            }),
+        None => HashSet::new(),
+    };
+
+    let target = args.target.as_ref().map(|path| {
+        TargetMask::load(path).unwrap_or_else(|err| {
+            eprintln!("Failed to load target {}: {err}", path.display());
+            std::process::exit(1);
+        })
+    });
+
+    let mut models = Vec::new();
+    let (positions, metadata, partial) = match (bundle_positions.or(imported), args.format, &args.solution_file) {
+        (Some(positions), ..) => (positions, SolutionMetadata::default(), false),
+        (None, SolutionFormat::Bin, Some(path)) => {
+            if args.watch {
+                eprintln!("--watch only follows text solutions");
+                std::process::exit(1);
+            }
+            let positions = binary::load(path).unwrap_or_else(|err| {
+                eprintln!("Failed to read binary solution {}: {err}", path.display());
+                std::process::exit(1);
+            });
+            // The solver's cost lines aren't part of the encoding.
+            (positions, SolutionMetadata::default(), false)
+        }
+        _ => {
+            let solution = read_solution(&args, &config);
+
+            // A watched file may still be half written; show what is there so far.
+            let (solution, partial) = if args.watch {
+                input::complete_part(&solution)
+            } else {
+                (solution.as_str(), false)
+            };
+
+            let parse = |solution| {
+                parse_sol(solution).unwrap_or_else(|err| {
+                    eprintln!("Failed to parse solution: {err}");
+                    std::process::exit(1);
+                })
+            };
+
+            // A watched file shows its latest model and follows the solver
+            // from there; otherwise every model is kept and the last, best
+            // one shown.
+            let positions = if args.watch {
+                parse(split_models(solution).last().unwrap())
+            } else {
+                models = split_models(solution).into_iter().map(parse).collect();
+                models.last().unwrap().clone()
+            };
+            (positions, parse_metadata(solution), partial)
+        }
+    };
+
+    // The last, best model unless the snapshot was looking at another.
+    let current = match state.solution {
+        Some(index) if index < models.len() => index,
+        _ => models.len().saturating_sub(1),
+    };
+    let positions = match models.get(current) {
+        Some(model) => model.clone(),
+        None => positions,
+    };
+
+    // A watched file fills up as it is written.
+    if !args.watch
+        && let Err(err) = validate_bounds(&positions, (config.width, config.height, config.depth))
+    {
+        eprintln!("Solution doesn't fit the box: {err}");
+        std::process::exit(1);
+    }
+
+    if let Some(layer) = args.only_layer
+        && !(1..=config.height).contains(&layer)
+    {
+        eprintln!("--only-layer {layer} is outside the box, whose layers go from 1 to {}", config.height);
+        std::process::exit(1);
+    }
+
+    if let Some(path) = &args.dump_bin {
+        if let Err(err) = binary::save(path, &positions) {
+            eprintln!("Failed to write binary solution {}: {err}", path.display());
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(path) = &args.save_bundle {
+        if let Err(err) = Bundle::new(&config, &positions).save(path) {
+            eprintln!("Failed to write bundle {}: {err}", path.display());
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(path) = &args.export_json {
+        if let Err(err) = SolutionJson::new(&positions).save(path) {
+            eprintln!("Failed to export JSON to {}: {err}", path.display());
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(path) = &args.export_path {
+        if let Err(err) = pipe_path::export(path, &positions) {
+            eprintln!("Failed to export the pipe path to {}: {err}", path.display());
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let offscreen = args.turntable.is_some()
+        || args.export_legend.is_some()
+        || args.export_section_sweep.is_some()
+        || args.export_depth.is_some()
+        || args.export_faces.is_some();
+
+    // In a window, --only-layer starts on that slice and the arrows move it
+    // from there; exports and offscreen renders leave the other layers out.
+    let positions = match args.only_layer {
+        Some(layer) if offscreen || args.export_web.is_some() || args.export_obj.is_some() => {
+            layer_slice(&positions, layer)
+        }
+        _ => positions,
+    };
+
+    if let Some(dir) = &args.export_web {
+        if let Err(err) = web::export(dir, &config, &positions) {
+            eprintln!("Failed to export the web viewer to {}: {err}", dir.display());
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(path) = &args.export_obj {
+        if let Err(err) = obj::export(path, &config, &positions) {
+            eprintln!("Failed to export OBJ to {}: {err}", path.display());
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(cell) = args.query {
+        match query::describe_cell(&positions, &config, cell) {
+            Ok(description) => println!("{description}"),
+            Err(err) => {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if args.headless {
+        print_summary(&config, &positions, &metadata);
+        return;
+    }
+
+    if !offscreen && !display_available() {
+        eprintln!(
+            "No display available to open a window; use --turntable, --export-legend, --export-section-sweep, --export-depth, --export-faces or --query for headless output, or --headless to only validate"
+        );
+        std::process::exit(1);
+    }
+
+    // let positions = parse_sol("block_pos(2,1,1,1,1) block_pos(1,2,1,4,1) block_pos(1,1,2,7,1) block_pos(3,1,2,6,1) block_pos(3,2,2,3,1) block_pos(2,1,3,8,1) block_pos(3,1,3,2,1) block_pos(3,3,3,5,1) block_pos(1,1,3,8,2) block_pos(2,1,2,8,4) block_pos(1,2,2,7,2) block_pos(3,1,1,6,4) block_pos(2,3,3,5,4) block_pos(1,3,1,4,2) block_pos(3,3,2,3,3) block_pos(3,2,3,2,2) block_pos(1,1,1,1,2) block_pos(3,3,1,3,2) block_pos(2,3,1,4,4) block_pos(1,3,2,4,3) block_pos(2,3,2,5,2) block_pos(1,3,3,5,3) block_pos(3,2,1,6,3) block_pos(1,2,3,7,4) block_pos(2,2,2,8,3) block_pos(2,2,3,7,3) block_pos(2,2,1,6,2) pipe_pos(2,1,1,e,a) pipe_pos(1,2,1,e,a) pipe_pos(1,2,2,e,n) pipe_pos(1,2,3,e,s) pipe_pos(2,2,2,e,w) pipe_pos(2,2,3,e,w) pipe_pos(2,2,1,b,w) pipe_pos(1,3,1,b,w) pipe_pos(3,2,2,b,w) pipe_pos(3,3,3,b,a) pipe_pos(2,2,1,w,b) pipe_pos(1,3,1,w,b) pipe_pos(3,2,2,w,b) pipe_pos(3,2,3,w,a) pipe_pos(3,1,1,w,n) pipe_pos(2,2,2,w,e) pipe_pos(2,2,3,w,e) pipe_pos(2,1,1,a,e) pipe_pos(1,2,1,a,e) pipe_pos(3,2,3,a,w) pipe_pos(3,1,2,a,s) pipe_pos(3,3,3,a,b) pipe_pos(1,2,2,n,e) pipe_pos(3,1,1,n,w) pipe_pos(3,1,2,s,a) pipe_pos(1,2,3,s,e)");
+
+    let mut app = App::new();
+    app.insert_resource(positions)
+        .insert_resource(metadata)
+        .insert_resource(config)
+        .insert_resource(keymap)
+        .insert_resource(ActiveLayer {
+            y: state.layer.or(args.only_layer).unwrap_or(1), // <-- starting layer
+            sliced: state.sliced.unwrap_or(args.only_layer.is_some()),
+        })
+        .add_systems(Startup, (setup, scale_reference::spawn_scale_reference));
+
+    // Quiet unless asked, so only warnings and errors reach a pipeline's
+    // stderr.
+    let log = if args.verbose {
+        LogPlugin {
+            level: Level::INFO,
+            filter: format!("{},renderer=debug", LogPlugin::default().filter),
+            ..default()
+        }
+    } else {
+        LogPlugin {
+            level: Level::WARN,
+            ..default()
+        }
+    };
+
+    if offscreen {
+        app.add_plugins(
+            DefaultPlugins
+                .set(log)
+                .set(WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    close_when_requested: false,
+                })
+                .disable::<WinitPlugin>(),
+        )
+        .add_plugins(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(1.0 / 60.0)))
+        .add_plugins(OffscreenPlugin);
+
+        if let Some(frames) = args.turntable {
+            app.add_plugins(TurntablePlugin { frames });
+        }
+        if let Some(path) = args.export_legend {
+            app.add_plugins(LegendPlugin { path });
+        }
+        if let Some(path) = args.export_depth {
+            app.add_plugins(DepthPlugin { path });
+        }
+        if let Some(dir) = args.export_faces {
+            if let Err(err) = std::fs::create_dir_all(&dir) {
+                eprintln!("Failed to create {}: {err}", dir.display());
+                std::process::exit(1);
+            }
+            app.add_plugins(FacesPlugin { dir });
+        }
+        if let Some(dir) = args.export_section_sweep {
+            if let Err(err) = std::fs::create_dir_all(&dir) {
+                eprintln!("Failed to create {}: {err}", dir.display());
+                std::process::exit(1);
+            }
+            app.add_plugins(SectionSweepPlugin {
+                dir,
+                axis: args.section_axis,
+                steps: args.section_steps,
+            });
+        }
+    } else {
+        // Wireframes are drawn with line polygons, which need asking for.
+        let render = RenderPlugin {
+            render_creation: WgpuSettings {
+                features: WgpuFeatures::POLYGON_MODE_LINE,
+                ..default()
+            }
+            .into(),
+            ..default()
+        };
+        app.add_plugins(DefaultPlugins.set(log).set(render))
+            .add_plugins(WireframePlugin)
+            .add_plugins(WireframeModePlugin)
+            .add_plugins(PanOrbitCameraPlugin)
+            .add_plugins(MeshPickingPlugin)
+            .add_plugins(HoverPlugin)
+            .add_plugins(CarvePlugin)
+            .add_plugins(ScreenshotPlugin)
+            .add_plugins(FocusPlugin {
+                radius: args.focus_radius,
+            })
+            .add_plugins(HudPlugin)
+            .add_plugins(LayerDiffPlugin)
+            .add_plugins(PipeCountsPlugin)
+            .add_plugins(BlockChartPlugin)
+            .add_plugins(DropPlugin {
+                speed: args.drop_speed,
+            })
+            .add_systems(Startup, spawn_camera)
+            .add_systems(Update, toggle_camera_controls_system)
+            .add_plugins(PipeFocusPlugin)
+            .add_plugins(PipeTubePlugin)
+            .add_plugins(NormalsPlugin)
+            .add_plugins(StraightRunPlugin)
+            .add_plugins(PipPlugin {
+                corner: args.pip_corner,
+                size: args.pip_size,
+                shown: state.pip.unwrap_or(args.pip),
+            })
+            .add_systems(Update, switch_layer_system) // <-- add this
+            .add_systems(Update, scale_reference::toggle_scale_reference_system);
+    }
+
+    if !offscreen && let Some(degrees) = args.snap_angle {
+        app.add_plugins(SnapPlugin {
+            angle: degrees.to_radians(),
+        });
+    }
+
+    if !offscreen && args.grow {
+        app.add_plugins(GrowPlugin);
+    }
+
+    if !offscreen && args.anaglyph {
+        app.add_plugins(AnaglyphPlugin {
+            separation: args.eye_separation,
+        });
+    }
+
+    if args.watch && let Some(path) = args.solution_file.clone() {
+        app.add_plugins(WatchPlugin { path, partial });
+    }
+
+    if args.pipe_colors == PipeColors::Pair {
+        app.add_plugins(PipePairsPlugin);
+    }
+
+    if args.instanced {
+        app.add_plugins(InstancingPlugin);
+    }
+
+    if let Some(environment) = args.environment {
+        app.add_plugins(EnvironmentPlugin {
+            environment,
+            top: args.gradient_top,
+            bottom: args.gradient_bottom,
+        });
+    }
+
+    app.add_plugins(ActivePlugin {
+        cells: active_cells,
+        color: args.active_color,
+        intensity: args.active_intensity,
+    })
+    .add_plugins(OverlaysPlugin)
+    .add_plugins(JunctionsPlugin)
+    .add_plugins(RecoveryPlugin)
+    .add_plugins(FootprintPlugin {
+        opacity: args.footprint_opacity,
+        shown: state.footprint.unwrap_or(args.footprint),
+    })
+    .add_plugins(AxesPlugin {
+        shown: state.axes.unwrap_or(args.axes),
+    })
+    .add_plugins(TransparentPlugin {
+        shown: state.transparent.unwrap_or(args.transparent),
+        alpha: state.opacity.unwrap_or(transparent::DEFAULT_ALPHA),
+    })
+    .add_plugins(StatePlugin {
+        save: args.save_state.clone(),
+        version: args.load_state.is_some().then_some(state.version),
+        camera: state.camera,
+        focus: state.focus,
+        wireframe: state.wireframe,
+    });
+
+    if let Some((&main, others)) = args.compare_dims.split_first() {
+        let others = others
+            .iter()
+            .map(|&dimensions| {
+                let (width, height, depth) = dimensions;
+                let solution = run_generator(dimensions).unwrap_or_else(|err| {
+                    eprintln!("Failed to run the generator for {width}x{height}x{depth}: {err}");
+                    std::process::exit(1);
+                });
+                let last = *split_models(&solution).last().unwrap();
+                let positions = parse_sol(last).unwrap_or_else(|err| {
+                    eprintln!("Failed to parse the {width}x{height}x{depth} solution: {err}");
+                    std::process::exit(1);
+                });
+                if let Err(err) = validate_bounds(&positions, dimensions) {
+                    eprintln!("The {width}x{height}x{depth} solution doesn't fit its box: {err}");
+                    std::process::exit(1);
+                }
+                (dimensions, positions)
+            })
+            .collect();
+        app.add_plugins(ComparePlugin { main, others });
+    }
+
+    if args.id_textures {
+        app.add_plugins(IdTexturesPlugin);
+    }
+
+    if let Some(style) = args.pipe_caps {
+        app.add_plugins(PipeCapsPlugin {
+            style,
+            color: args.cap_color,
+        });
+    }
+
+    if let Some(seconds_per_frame) = args.playback {
+        if models.len() < 2 {
+            eprintln!("--playback needs a solver output with several models");
+            std::process::exit(1);
+        }
+        app.add_plugins(PlaybackPlugin {
+            models,
+            seconds_per_frame,
+        });
+    } else if models.len() > 1 && !args.grow {
+        app.add_plugins(BlinkPlugin {
+            models,
+            interval: args.blink,
+            current,
+        });
+    }
+
+    if args.scalar_field.is_some() {
+        app.add_plugins(ScalarFieldPlugin);
+    }
+
+    if let Some(target) = target {
+        app.insert_resource(target).add_plugins(TargetPlugin);
+    }
+
+    if let Some(secs) = args.exit_after {
+        app.insert_resource(ExitTimer(Timer::from_seconds(secs, TimerMode::Once)))
+            .add_systems(Update, exit_after_system);
+    }
+
+    app.run();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overflowing_coordinate_is_an_invalid_number() {
+        let err = parse_sol("block_pos(1,99999999999999999999999,1,1,1)").unwrap_err();
+        assert!(matches!(
+            err,
+            ParseSolError::InvalidNumber { field: "y", ref token, .. } if token == "99999999999999999999999"
+        ));
+    }
+
+    #[test]
+    fn negative_coordinate_is_an_invalid_number() {
+        let err = parse_sol("pipe_pos(-1,1,1,w,e)").unwrap_err();
+        assert!(matches!(err, ParseSolError::InvalidNumber { field: "x", .. }));
+    }
+
+    #[test]
+    fn opposite_of_every_direction() {
+        let opposites = [
+            (Direction::Above, Direction::Below),
+            (Direction::Below, Direction::Above),
+            (Direction::North, Direction::South),
+            (Direction::South, Direction::North),
+            (Direction::East, Direction::West),
+            (Direction::West, Direction::East),
+        ];
+        for (direction, opposite) in opposites {
+            assert_eq!(direction.opposite(), opposite);
+            assert_eq!(direction.opposite().opposite(), direction);
+        }
+    }
+
+    #[test]
+    fn all_lists_every_direction_once_in_declaration_order() {
+        let all: Vec<Direction> = Direction::all().collect();
+        assert_eq!(
+            all,
+            [
+                Direction::Above,
+                Direction::Below,
+                Direction::North,
+                Direction::South,
+                Direction::East,
+                Direction::West,
+            ]
+        );
+        let distinct: HashSet<Direction> = all.iter().copied().collect();
+        assert_eq!(distinct.len(), 6);
+    }
+
+    #[test]
+    fn axis_colors_default_to_red_green_blue() {
+        let args = Args::parse_from(["renderer"]);
+        let [x, y, z] = args.axis_colors.map(|color| color.to_srgba());
+        assert!(x.red > x.green && x.red > x.blue);
+        assert!(y.green > y.red && y.green > y.blue);
+        assert!(z.blue > z.red && z.blue > z.green);
+
+        assert!(parse_axis_colors("ff0000,00ff00").is_err());
+        assert!(parse_axis_colors("ff0000,00ff00,blue").is_err());
+    }
+
+    #[test]
+    fn symbolic_block_ids_parse() {
+        let positions = parse_sol("block_pos(1,1,1,b_a,0) block_pos(2,1,1,\"red corner\",1)").unwrap();
+        assert_eq!(positions.block_id(&(1, 1, 1)).map(String::as_str), Some("b_a"));
+        assert_eq!(positions.block_id(&(2, 1, 1)).map(String::as_str), Some("red corner"));
+        assert_eq!(positions.positions[&(1, 1, 1)].unit, 0);
+    }
+
+    #[test]
+    fn second_block_in_a_cell_overlaps() {
+        // The same block repeated is fine.
+        assert!(parse_sol("block_pos(1,1,1,b_a,1) block_pos(1,1,1,b_a,1)").is_ok());
+
+        let err = parse_sol("block_pos(1,1,1,b_a,1) block_pos(1,1,1,b_b,1)").unwrap_err();
+        assert!(matches!(
+            err,
+            ParseSolError::Overlap { index: 2, cell: (1, 1, 1), ref other, .. } if other == "b_a"
+        ));
+    }
 }
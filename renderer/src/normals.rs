@@ -0,0 +1,93 @@
+//! Debug view of face orientation, toggled with F3: every triangle of the
+//! visible meshes gets a short line along the normal implied by its
+//! winding, green when the mesh's own vertex normals agree and red when
+//! they point the other way, to catch inside-out geometry.
+
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, VertexAttributeValues},
+};
+
+use crate::{
+    instancing::InstancedBlocks,
+    keymap::{Action, Keymap},
+};
+
+const NORMAL_LENGTH: f32 = 0.2;
+const AGREES_COLOR: Color = Color::srgb(0.2, 1.0, 0.2);
+const DISAGREES_COLOR: Color = Color::srgb(1.0, 0.1, 0.1);
+
+pub struct NormalsPlugin;
+
+impl Plugin for NormalsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShowNormals>()
+            .add_systems(Update, (toggle_normals_system, draw_normals_system).chain());
+    }
+}
+
+#[derive(Resource, Default)]
+struct ShowNormals(bool);
+
+fn toggle_normals_system(
+    key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut show: ResMut<ShowNormals>,
+) {
+    if keymap.just_pressed(Action::Normals, &key_input) {
+        show.0 = !show.0;
+    }
+}
+
+fn draw_normals_system(
+    show: Res<ShowNormals>,
+    meshes: Res<Assets<Mesh>>,
+    // The instanced mesh is drawn once per cell, not at its own transform.
+    query: Query<(&Mesh3d, &GlobalTransform, &ViewVisibility), Without<InstancedBlocks>>,
+    mut gizmos: Gizmos,
+) {
+    if !show.0 {
+        return;
+    }
+
+    for (mesh, transform, visibility) in &query {
+        if !visibility.get() {
+            continue;
+        }
+        let Some(mesh) = meshes.get(&mesh.0) else {
+            continue;
+        };
+        let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+            continue;
+        };
+        let normals = match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+            Some(VertexAttributeValues::Float32x3(normals)) => Some(normals),
+            _ => None,
+        };
+
+        let indices: Vec<usize> = match mesh.indices() {
+            Some(Indices::U16(indices)) => indices.iter().map(|&i| i as usize).collect(),
+            Some(Indices::U32(indices)) => indices.iter().map(|&i| i as usize).collect(),
+            None => (0..positions.len()).collect(),
+        };
+
+        for triangle in indices.chunks_exact(3) {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|i| Vec3::from(positions[i]));
+            let Some(winding) = (b - a).cross(c - a).try_normalize() else {
+                continue;
+            };
+            let stored: Vec3 = normals.map_or(winding, |normals| {
+                triangle.iter().map(|&i| Vec3::from(normals[i])).sum()
+            });
+            let color = if stored.dot(winding) >= 0.0 {
+                AGREES_COLOR
+            } else {
+                DISAGREES_COLOR
+            };
+
+            let center = transform.transform_point((a + b + c) / 3.0);
+            let normal = transform.affine().transform_vector3(winding).normalize_or_zero();
+            gizmos.line(center, center + normal * NORMAL_LENGTH, color);
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! `--export-obj <path>`: writes the blocks and pipes as a Wavefront OBJ,
+//! with their colors in an MTL file next to it, for rendering in Blender
+//! or another tool.
+//!
+//! Every piece is the mesh the renderer would spawn, moved by the same
+//! transform, so the export lines up with the app in the same world
+//! coordinates. Blocks are grouped by id and cells are whole cubes, faces
+//! hidden between them included; the random jitter is left out.
+
+use std::{
+    collections::HashSet,
+    fmt::Write as _,
+    fs, io,
+    path::Path,
+};
+
+use bevy::{prelude::*, render::mesh::VertexAttributeValues};
+
+use crate::{BlockInfo, Config, Direction, PipeQuality, PipeShape, PipeStyle, Positions, id_color, pipe_mesh};
+
+const PIPE_COLOR: Color = Color::srgb(1.0, 0.0, 0.0);
+const PIPE_MATERIAL: &str = "pipe";
+
+/// OBJ names can't hold spaces, and ids are free text.
+fn material_name(id: &str) -> String {
+    let name: String = id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("block_{name}")
+}
+
+#[derive(Default)]
+struct ObjWriter {
+    obj: String,
+    /// Vertices written so far; OBJ indices count from 1 across the file.
+    vertices: usize,
+}
+
+impl ObjWriter {
+    fn group(&mut self, name: &str, material: &str) {
+        let _ = writeln!(self.obj, "g {name}\nusemtl {material}");
+    }
+
+    fn mesh(&mut self, mesh: &Mesh, transform: Transform) {
+        let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+            return;
+        };
+        let normals = match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+            Some(VertexAttributeValues::Float32x3(normals)) => normals.as_slice(),
+            _ => &[],
+        };
+
+        for &position in positions {
+            let [x, y, z] = transform.transform_point(position.into()).to_array();
+            let _ = writeln!(self.obj, "v {x} {y} {z}");
+        }
+        for &normal in normals {
+            let [x, y, z] = (transform.rotation * Vec3::from(normal)).to_array();
+            let _ = writeln!(self.obj, "vn {x} {y} {z}");
+        }
+
+        let indices: Vec<usize> = match mesh.indices() {
+            Some(indices) => indices.iter().collect(),
+            None => (0..positions.len()).collect(),
+        };
+        for triangle in indices.chunks_exact(3) {
+            let corner = |&index: &usize| {
+                let index = self.vertices + index + 1;
+                if normals.is_empty() {
+                    index.to_string()
+                } else {
+                    format!("{index}//{index}")
+                }
+            };
+            let corners: Vec<String> = triangle.iter().map(corner).collect();
+            let _ = writeln!(self.obj, "f {}", corners.join(" "));
+        }
+        self.vertices += positions.len();
+    }
+}
+
+/// The pieces `spawn_solution` draws for the segment `(in_dir, out_dir)`
+/// of cell `(x, y, z)`, spans already drawn from the neighbor left out.
+fn segment_pieces(
+    config: &Config,
+    (x, y, z): (usize, usize, usize),
+    (in_dir, out_dir): (Direction, Direction),
+    spans: &mut HashSet<IVec3>,
+) -> Vec<(Mesh, Transform)> {
+    let center = config.cell_position((x, y, z));
+    if config.pipe_style == PipeStyle::Span {
+        return [in_dir, out_dir]
+            .into_iter()
+            .filter(|dir| spans.insert((center * 2.0 + dir.offset()).as_ivec3()))
+            .map(|dir| {
+                let transform = Transform::from_translation(center + dir.offset() * 0.5);
+                match config.pipe_shape {
+                    PipeShape::Cuboid => (Cuboid::from_size(Vec3::splat(0.3) + dir.offset().abs()).into(), transform),
+                    PipeShape::Cylinder => (
+                        Cylinder::new(config.pipe_radius, 1.3).into(),
+                        transform.with_rotation(dir.pipe_rotation()),
+                    ),
+                }
+            })
+            .collect();
+    }
+
+    match (config.pipe_shape, config.pipe_quality) {
+        (PipeShape::Cylinder, _) => [in_dir, out_dir]
+            .into_iter()
+            .map(|dir| {
+                let transform = Transform::from_translation(center + dir.offset() * 0.25);
+                (Cylinder::new(config.pipe_radius, 0.5).into(), transform.with_rotation(dir.pipe_rotation()))
+            })
+            .collect(),
+        (PipeShape::Cuboid, PipeQuality::High) => {
+            vec![(pipe_mesh::segment_mesh(in_dir, out_dir), Transform::from_translation(center))]
+        }
+        (PipeShape::Cuboid, PipeQuality::Low) => [in_dir, out_dir]
+            .into_iter()
+            .map(|dir| {
+                let size = Vec3::new(dir.pipe_width(), dir.pipe_height(), dir.pipe_depth());
+                let mut transform = dir.pipe_transform(x as f32, y as f32, z as f32);
+                transform.translation += config.origin;
+                (Cuboid::from_size(size).into(), transform)
+            })
+            .collect(),
+    }
+}
+
+fn material(mtl: &mut String, name: &str, color: Color) {
+    let Srgba { red, green, blue, .. } = color.to_srgba();
+    let _ = writeln!(mtl, "newmtl {name}\nKd {red} {green} {blue}\n");
+}
+
+pub fn export(path: &Path, config: &Config, positions: &Positions) -> io::Result<()> {
+    let mtl_path = path.with_extension("mtl");
+    let mut writer = ObjWriter::default();
+    let mut mtl = String::new();
+    if let Some(name) = mtl_path.file_name() {
+        let _ = writeln!(writer.obj, "mtllib {}", name.to_string_lossy());
+    }
+
+    // Sorted, so the same solution always exports the same file.
+    let mut cells: Vec<_> = positions.positions.iter().collect();
+    cells.sort_by(|(a_cell, a), (b_cell, b)| a.id.cmp(&b.id).then(a_cell.cmp(b_cell)));
+    let cube: Mesh = Cuboid::new(1.0, 1.0, 1.0).into();
+    let mut previous = None;
+    for (&cell, BlockInfo { id, orientation, .. }) in cells {
+        if previous != Some(id) {
+            let name = material_name(id);
+            writer.group(&name, &name);
+            material(&mut mtl, &name, id_color(id, config));
+            previous = Some(id);
+        }
+        let transform = Transform::from_translation(config.cell_position(cell)).with_rotation(orientation.rotation());
+        writer.mesh(&cube, transform);
+    }
+
+    let mut pipe_cells: Vec<_> = positions.pipe_positions.iter().collect();
+    pipe_cells.sort();
+    if !pipe_cells.is_empty() {
+        writer.group("pipes", PIPE_MATERIAL);
+        material(&mut mtl, PIPE_MATERIAL, PIPE_COLOR);
+    }
+    let joint: Mesh = Sphere::new(config.joint_radius()).into();
+    let mut spans = HashSet::new();
+    for (&cell, segments) in pipe_cells {
+        if !segments.is_empty() && !config.mitered_pipes() {
+            writer.mesh(&joint, Transform::from_translation(config.cell_position(cell)));
+        }
+        for &segment in segments {
+            for (mesh, transform) in segment_pieces(config, cell, segment, &mut spans) {
+                writer.mesh(&mesh, transform);
+            }
+        }
+    }
+
+    fs::write(path, writer.obj)?;
+    fs::write(mtl_path, mtl)
+}
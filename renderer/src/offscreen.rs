@@ -0,0 +1,186 @@
+//! Shared pieces of the headless exports: an image to render into, a
+//! signal for when the renderer is actually able to draw into it, and the
+//! loop capturing a sequence of frames one at a time.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use bevy::{
+    image::BevyDefault,
+    prelude::*,
+    render::{
+        Render, RenderApp, RenderSet,
+        render_asset::RenderAssetUsages,
+        render_resource::{
+            CachedPipelineState, Extent3d, PipelineCache, TextureDimension, TextureFormat,
+            TextureUsages,
+        },
+        view::screenshot::{Screenshot, ScreenshotCaptured},
+    },
+};
+
+/// Frames rendered before the first capture, so meshes, materials and
+/// text are on the GPU by the time the first image is written.
+pub const WARMUP_FRAMES: u32 = 10;
+
+/// Frames between posing a frame and capturing it, so entities that just
+/// came into view have been queued for drawing.
+pub const SETTLE_FRAMES: u32 = 2;
+
+pub struct OffscreenPlugin;
+
+impl Plugin for OffscreenPlugin {
+    fn build(&self, app: &mut App) {
+        let ready = PipelinesReady::default();
+        app.sub_app_mut(RenderApp)
+            .insert_resource(ready.clone())
+            .add_systems(Render, update_pipelines_ready.in_set(RenderSet::Cleanup));
+        app.insert_resource(ready);
+    }
+}
+
+/// Whether the render world has finished compiling every queued pipeline.
+/// Shaders compile in the background, and until they are done the cameras
+/// draw nothing, so a fixed warmup alone can still yield a blank capture.
+#[derive(Resource, Clone, Default)]
+pub struct PipelinesReady(Arc<AtomicBool>);
+
+impl PipelinesReady {
+    pub fn get(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+fn update_pipelines_ready(pipeline_cache: Res<PipelineCache>, ready: Res<PipelinesReady>) {
+    let compiling = pipeline_cache.pipelines().any(|pipeline| {
+        matches!(
+            pipeline.state,
+            CachedPipelineState::Queued | CachedPipelineState::Creating(_)
+        )
+    });
+    ready.0.store(!compiling, Ordering::Relaxed);
+}
+
+/// A blank image usable as a camera's render target and screenshot source.
+pub fn render_target(images: &mut Assets<Image>, width: u32, height: u32) -> Handle<Image> {
+    let size = Extent3d {
+        width,
+        height,
+        ..default()
+    };
+
+    let mut image = Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0; 4],
+        TextureFormat::bevy_default(),
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage |=
+        TextureUsages::COPY_SRC | TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING;
+    images.add(image)
+}
+
+/// Progress of an export writing `frames` images, kept on its camera.
+///
+/// One frame is in flight at a time: the exporter poses it, it settles,
+/// it is captured, and the next one waits until the capture has landed, so
+/// frames are written in order.
+#[derive(Component)]
+pub struct FrameSequence {
+    frames: u32,
+    warmup: u32,
+    settle: u32,
+    next: u32,
+    posed: bool,
+    saved: u32,
+    pending: bool,
+}
+
+/// What an exporter does with its frame sequence this frame.
+pub enum FrameStep {
+    /// Nothing yet.
+    Wait,
+    /// Set the scene up for frame `n`.
+    Pose(u32),
+    /// Capture frame `n` with [`capture`].
+    Capture(u32),
+    /// Every frame has been written.
+    Done,
+}
+
+impl FrameSequence {
+    pub fn new(frames: u32) -> FrameSequence {
+        FrameSequence {
+            frames,
+            warmup: WARMUP_FRAMES,
+            settle: 0,
+            next: 0,
+            posed: false,
+            saved: 0,
+            pending: false,
+        }
+    }
+
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// Advances the sequence by one rendered frame.
+    pub fn step(&mut self, ready: &PipelinesReady) -> FrameStep {
+        if self.warmup > 0 {
+            self.warmup -= 1;
+            return FrameStep::Wait;
+        }
+
+        // Pipelines for entities coming into view can also be queued later
+        // on, so this is checked before every capture, not only after
+        // warmup.
+        if !ready.get() {
+            return FrameStep::Wait;
+        }
+
+        if self.saved == self.frames {
+            return FrameStep::Done;
+        }
+
+        if self.pending || self.next == self.frames {
+            return FrameStep::Wait;
+        }
+
+        if !self.posed {
+            self.posed = true;
+            self.settle = SETTLE_FRAMES;
+            return FrameStep::Pose(self.next);
+        }
+
+        if self.settle > 0 {
+            self.settle -= 1;
+            return FrameStep::Wait;
+        }
+
+        let frame = self.next;
+        self.next += 1;
+        self.posed = false;
+        self.pending = true;
+        FrameStep::Capture(frame)
+    }
+}
+
+/// Screenshots `target` for the frame sequence on `camera`, moving it on
+/// once the image has been captured. What to do with the image is left to
+/// observers added to the returned screenshot.
+pub fn capture<'a>(commands: &'a mut Commands, camera: Entity, target: &Handle<Image>) -> EntityCommands<'a> {
+    let mut screenshot = commands.spawn(Screenshot::image(target.clone()));
+    screenshot.observe(
+        move |_trigger: Trigger<ScreenshotCaptured>, mut sequences: Query<&mut FrameSequence>| {
+            if let Ok(mut sequence) = sequences.get_mut(camera) {
+                sequence.saved += 1;
+                sequence.pending = false;
+            }
+        },
+    );
+    screenshot
+}
@@ -0,0 +1,224 @@
+//! Block material overlays: modes that change how some blocks are drawn on
+//! top of their color. Rather than each putting its own material on the
+//! blocks and dropping the others', every block's material is resolved
+//! here from its color and all the overlays at once, in a fixed order:
+//!
+//! 1. pipe counts replace the color with that of the cell's count
+//!    (`pipe_counts`),
+//! 2. the layer diff replaces it with its highlight (`layer_diff`),
+//! 3. the active cells glow in whatever color they have (`active`),
+//! 4. blocks outside the focus fade, whatever they show (`focus`).
+//!
+//! The overlays only change their own state, before
+//! [`BlockMaterialSet::Overlays`]. Modes that swap the resolved material
+//! out for a while, such as wireframe cages and playback fades, do so in
+//! [`BlockMaterialSet::Swaps`], after it.
+
+use std::collections::HashMap;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    ActiveLayer, Block, BlockMaterials, Positions,
+    active::{ActiveCells, ActiveGlow},
+    focus::{self, Focus},
+    layer_diff::LayerDiff,
+    pipe_counts::PipeCounts,
+};
+
+pub struct OverlaysPlugin;
+
+impl Plugin for OverlaysPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(Update, (BlockMaterialSet::Overlays, BlockMaterialSet::Swaps).chain())
+            .init_resource::<DerivedMaterials>()
+            .add_systems(Update, resolve_block_materials_system.in_set(BlockMaterialSet::Overlays));
+    }
+}
+
+/// Systems choosing block materials.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BlockMaterialSet {
+    /// Every block's material resolved from its color and the overlays.
+    Overlays,
+    /// Modes putting their own material on resolved blocks for a while.
+    Swaps,
+}
+
+/// Copy of each material with an overlay applied, keyed by the original.
+#[derive(Resource, Default)]
+struct DerivedMaterials {
+    glowing: HashMap<AssetId<StandardMaterial>, Handle<StandardMaterial>>,
+    dimmed: HashMap<AssetId<StandardMaterial>, Handle<StandardMaterial>>,
+}
+
+fn derive(
+    cache: &mut HashMap<AssetId<StandardMaterial>, Handle<StandardMaterial>>,
+    materials: &mut Assets<StandardMaterial>,
+    source: &Handle<StandardMaterial>,
+    overlay: impl FnOnce(&mut StandardMaterial),
+) -> Handle<StandardMaterial> {
+    cache
+        .entry(source.id())
+        .or_insert_with(|| {
+            let mut derived = materials.get(source).cloned().unwrap_or_default();
+            overlay(&mut derived);
+            materials.add(derived)
+        })
+        .clone()
+}
+
+/// The state of every overlay, each missing while its mode isn't built in.
+#[derive(SystemParam)]
+struct Overlays<'w> {
+    pipe_counts: Option<Res<'w, PipeCounts>>,
+    layer_diff: Option<Res<'w, LayerDiff>>,
+    active_layer: Option<Res<'w, ActiveLayer>>,
+    active: Option<Res<'w, ActiveCells>>,
+    glow: Option<Res<'w, ActiveGlow>>,
+    focus: Option<Res<'w, Focus>>,
+}
+
+impl Overlays<'_> {
+    fn is_changed(&self) -> bool {
+        fn changed<T: Resource>(resource: &Option<Res<T>>) -> bool {
+            resource.as_ref().is_some_and(|resource| resource.is_changed())
+        }
+        // Moving the layer with the diff off changes nothing.
+        let layer_moved = changed(&self.active_layer)
+            && self.layer_diff.as_ref().is_some_and(|layer_diff| layer_diff.enabled);
+        changed(&self.pipe_counts)
+            || changed(&self.layer_diff)
+            || layer_moved
+            || changed(&self.active)
+            || changed(&self.focus)
+    }
+}
+
+fn resolve_block_materials_system(
+    positions: Res<Positions>,
+    block_materials: Res<BlockMaterials>,
+    overlays: Overlays,
+    mut derived: ResMut<DerivedMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut blocks: Query<(Ref<Block>, &mut MeshMaterial3d<StandardMaterial>)>,
+) {
+    // Otherwise only blocks respawned by --watch or blink are resolved.
+    let changed = block_materials.is_changed() || overlays.is_changed();
+    let Overlays {
+        pipe_counts,
+        layer_diff,
+        active_layer,
+        active,
+        glow,
+        focus,
+    } = &overlays;
+
+    let DerivedMaterials { glowing, dimmed } = &mut *derived;
+    for (block, mut material) in blocks.iter_mut() {
+        if !changed && !block.is_added() {
+            continue;
+        }
+
+        let cell = (block.x, block.y, block.z);
+        let Some(id) = positions.block_id(&cell) else {
+            continue;
+        };
+        let mut resolved = block_materials.cell(id, cell);
+        if let Some(pipe_counts) = pipe_counts
+            && let Some(counted) = pipe_counts.material(&positions, cell)
+        {
+            resolved = counted;
+        }
+        if let (Some(layer_diff), Some(active_layer)) = (layer_diff, active_layer)
+            && let Some(highlight) = layer_diff.material(&positions, active_layer, cell)
+        {
+            resolved = highlight;
+        }
+        let mut resolved = resolved.clone();
+        if let (Some(active), Some(glow)) = (active, glow)
+            && active.cells.contains(&cell)
+        {
+            resolved = derive(glowing, &mut materials, &resolved, |material| glow.apply(material));
+        }
+        if focus.as_ref().is_some_and(|focus| focus.dims(cell)) {
+            resolved = derive(dimmed, &mut materials, &resolved, focus::dim);
+        }
+
+        if material.0 != resolved {
+            material.0 = resolved;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use bevy::asset::AssetPlugin;
+
+    use super::*;
+    use crate::{active::ActivePlugin, keymap::Keymap, parse_sol};
+
+    fn material(app: &App, block: Entity) -> &StandardMaterial {
+        let handle = app.world().get::<MeshMaterial3d<StandardMaterial>>(block).unwrap();
+        app.world().resource::<Assets<StandardMaterial>>().get(handle).unwrap()
+    }
+
+    #[test]
+    fn active_glow_survives_a_focus_change() {
+        let glow = LinearRgba::rgb(0.0, 4.0, 0.0);
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            ActivePlugin {
+                cells: HashSet::from([(1, 1, 1), (3, 1, 1)]),
+                color: Color::from(glow),
+                intensity: 1.0,
+            },
+            OverlaysPlugin,
+        ))
+        .init_asset::<StandardMaterial>()
+        .insert_resource(Keymap::default())
+        .insert_resource(parse_sol("block_pos(1,1,1,a,1) block_pos(2,1,1,b,1) block_pos(3,1,1,c,1)").unwrap())
+        .insert_resource(Focus {
+            enabled: true,
+            center: Some((1, 1, 1)),
+            radius: 2,
+        });
+
+        let mut materials = app.world_mut().resource_mut::<Assets<StandardMaterial>>();
+        let by_id: HashMap<String, Handle<StandardMaterial>> =
+            ["a", "b", "c"].map(|id| (id.to_string(), materials.add(Color::WHITE))).into();
+        let fallback = materials.add(Color::WHITE);
+        app.insert_resource(BlockMaterials {
+            by_id: by_id.clone(),
+            striped: default(),
+            by_cell: default(),
+            fallback,
+        });
+        let blocks = [1, 2, 3].map(|x| {
+            let id = ["a", "b", "c"][x - 1];
+            app.world_mut()
+                .spawn((Block { x, y: 1, z: 1 }, MeshMaterial3d(by_id[id].clone())))
+                .id()
+        });
+        app.update();
+
+        assert_eq!(material(&app, blocks[0]).emissive, glow);
+        assert_eq!(material(&app, blocks[1]).emissive, LinearRgba::BLACK);
+        assert_eq!(material(&app, blocks[2]).alpha_mode, AlphaMode::Opaque);
+
+        // Shrinking the focus fades the far block, which keeps its glow.
+        app.world_mut().resource_mut::<Focus>().radius = 1;
+        app.update();
+
+        let [near, middle, far] = blocks.map(|block| material(&app, block).clone());
+        assert_eq!(near.emissive, glow);
+        assert_eq!(near.alpha_mode, AlphaMode::Opaque);
+        assert_eq!(middle.emissive, LinearRgba::BLACK);
+        assert_eq!(far.emissive, glow);
+        assert_eq!(far.alpha_mode, AlphaMode::Blend);
+    }
+}
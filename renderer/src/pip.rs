@@ -0,0 +1,182 @@
+//! Picture-in-picture: a small inset in one corner of the window showing
+//! the whole box from a fixed overview while the main camera roams.
+//! Toggled with I, or on from the start with `--pip`; `--pip-corner` and
+//! `--pip-size` place it.
+//!
+//! The inset camera renders after the main one without clearing, over a
+//! panel the main camera draws as part of the HUD, so it gets its own
+//! background without wiping out the main view.
+
+use std::f32::consts::TAU;
+
+use bevy::{
+    prelude::*,
+    render::{camera::Viewport, renderer::RenderAdapter},
+    window::PrimaryWindow,
+};
+
+use crate::{
+    Config, camera,
+    keymap::{Action, Keymap},
+};
+
+/// Gap between the inset and the edges of the window, as a fraction of its
+/// size.
+const MARGIN: f32 = 0.02;
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PipCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+pub struct PipPlugin {
+    pub corner: PipCorner,
+    /// Fraction of the window width and height the inset takes up.
+    pub size: f32,
+    pub shown: bool,
+}
+
+impl Plugin for PipPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Pip {
+            corner: self.corner,
+            size: self.size,
+            shown: self.shown,
+        })
+        .add_systems(Startup, spawn_pip)
+        .add_systems(Update, (toggle_pip_system, place_pip_system).chain());
+    }
+}
+
+#[derive(Resource)]
+pub struct Pip {
+    corner: PipCorner,
+    size: f32,
+    pub shown: bool,
+}
+
+#[derive(Component)]
+struct PipCamera;
+
+#[derive(Component)]
+struct PipPanel;
+
+pub fn parse_pip_size(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(size) if size > 0.0 && size <= 1.0 - 2.0 * MARGIN => Ok(size),
+        _ => Err(format!(
+            "expected a fraction of the window above 0 and at most {}; got `{s}`",
+            1.0 - 2.0 * MARGIN
+        )),
+    }
+}
+
+impl Pip {
+    /// Offset of the inset's top-left corner from the window's, as
+    /// fractions of its size.
+    fn origin(&self) -> Vec2 {
+        let far = 1.0 - MARGIN - self.size;
+        match self.corner {
+            PipCorner::TopLeft => Vec2::new(MARGIN, MARGIN),
+            PipCorner::TopRight => Vec2::new(far, MARGIN),
+            PipCorner::BottomLeft => Vec2::new(MARGIN, far),
+            PipCorner::BottomRight => Vec2::new(far, far),
+        }
+    }
+}
+
+fn spawn_pip(
+    mut commands: Commands,
+    pip: Res<Pip>,
+    config: Res<Config>,
+    clear_color: Res<ClearColor>,
+    adapter: Res<RenderAdapter>,
+) {
+    commands.spawn((
+        Camera3d::default(),
+        Camera {
+            order: 1,
+            is_active: pip.shown,
+            clear_color: ClearColorConfig::None,
+            ..default()
+        },
+        camera::orbit_transform(
+            camera::box_center(&config),
+            TAU / 8.0,
+            TAU / 8.0,
+            camera::framing_distance(&config, config.fov),
+        ),
+        camera::projection(&config),
+        camera::supported_msaa(config.msaa, &adapter),
+        PipCamera,
+    ));
+
+    let origin = pip.origin();
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(origin.x * 100.0),
+            top: Val::Percent(origin.y * 100.0),
+            width: Val::Percent(pip.size * 100.0),
+            height: Val::Percent(pip.size * 100.0),
+            border: UiRect::all(Val::Px(1.0)),
+            display: if pip.shown { Display::Flex } else { Display::None },
+            ..default()
+        },
+        BackgroundColor(clear_color.0.darker(0.05)),
+        BorderColor(Color::srgb(0.6, 0.6, 0.6)),
+        PipPanel,
+    ));
+}
+
+fn toggle_pip_system(
+    key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut pip: ResMut<Pip>,
+    mut cameras: Query<&mut Camera, With<PipCamera>>,
+    mut panels: Query<&mut Node, With<PipPanel>>,
+) {
+    if !keymap.just_pressed(Action::Pip, &key_input) {
+        return;
+    }
+
+    pip.shown = !pip.shown;
+    for mut camera in cameras.iter_mut() {
+        camera.is_active = pip.shown;
+    }
+    for mut node in panels.iter_mut() {
+        node.display = if pip.shown { Display::Flex } else { Display::None };
+    }
+}
+
+/// Keeps the viewport over the panel as the window is resized.
+fn place_pip_system(
+    pip: Res<Pip>,
+    window: Option<Single<&Window, With<PrimaryWindow>>>,
+    mut cameras: Query<&mut Camera, With<PipCamera>>,
+) {
+    let Some(window) = window else {
+        return;
+    };
+    if !pip.shown {
+        return;
+    }
+
+    let window_size = window.physical_size().as_vec2();
+    let viewport = Viewport {
+        physical_position: (pip.origin() * window_size).as_uvec2(),
+        physical_size: (pip.size * window_size).max(Vec2::ONE).as_uvec2(),
+        ..default()
+    };
+    for mut camera in cameras.iter_mut() {
+        if camera.viewport.as_ref().is_none_or(|current| {
+            current.physical_position != viewport.physical_position
+                || current.physical_size != viewport.physical_size
+        }) {
+            camera.viewport = Some(viewport.clone());
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! `--pipe-caps flat|flared`: marks where the pipes leave the box with a
+//! cap on the face they pass through, so the inlet and outlet are easy to
+//! spot from any side.
+//!
+//! A cap goes on a segment that points out through a face of the box and
+//! isn't met by a segment from the other side. Ends that stop inside the
+//! box are left alone; they aren't open to the outside.
+
+use bevy::prelude::*;
+
+use crate::{Config, Positions, pipe_path};
+
+/// Wider than the pipe cross-section so the rim shows around it.
+const CAP_RADIUS: f32 = 0.25;
+const FLAT_THICKNESS: f32 = 0.04;
+const FLARE_LENGTH: f32 = 0.15;
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapStyle {
+    /// A thin disc on the face
+    Flat,
+    /// A funnel widening out from the pipe to the face
+    Flared,
+}
+
+pub struct PipeCapsPlugin {
+    pub style: CapStyle,
+    pub color: Color,
+}
+
+impl Plugin for PipeCapsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CapSettings {
+            style: self.style,
+            color: self.color,
+        })
+        .add_systems(Startup, setup_caps)
+        .add_systems(Update, spawn_caps_system);
+    }
+}
+
+#[derive(Resource)]
+struct CapSettings {
+    style: CapStyle,
+    color: Color,
+}
+
+#[derive(Resource)]
+struct CapAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+struct PipeCap;
+
+/// Along +Y, the outward side at the top.
+fn cap_mesh(style: CapStyle, config: &Config) -> Mesh {
+    match style {
+        CapStyle::Flat => Cylinder::new(CAP_RADIUS, FLAT_THICKNESS).into(),
+        CapStyle::Flared => ConicalFrustum {
+            radius_top: CAP_RADIUS,
+            radius_bottom: config.joint_radius(),
+            height: FLARE_LENGTH,
+        }
+        .into(),
+    }
+}
+
+fn setup_caps(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    settings: Res<CapSettings>,
+    config: Res<Config>,
+) {
+    let alpha_mode = if settings.color.alpha() < 1.0 {
+        AlphaMode::Blend
+    } else {
+        AlphaMode::Opaque
+    };
+    commands.insert_resource(CapAssets {
+        mesh: meshes.add(cap_mesh(settings.style, &config)),
+        material: materials.add(StandardMaterial {
+            base_color: settings.color,
+            alpha_mode,
+            ..default()
+        }),
+    });
+}
+
+fn spawn_caps_system(
+    mut commands: Commands,
+    positions: Res<Positions>,
+    config: Res<Config>,
+    settings: Res<CapSettings>,
+    assets: Res<CapAssets>,
+    spawned: Query<Entity, With<PipeCap>>,
+) {
+    if !positions.is_changed() {
+        return;
+    }
+    for entity in spawned.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    // Flush with the face, or ending on it for the funnel.
+    let depth = match settings.style {
+        CapStyle::Flat => FLAT_THICKNESS / 2.0,
+        CapStyle::Flared => FLARE_LENGTH / 2.0,
+    };
+    for (&(x, y, z), segments) in &positions.pipe_positions {
+        let open = segments
+            .iter()
+            .flat_map(|&(in_dir, out_dir)| [in_dir, out_dir])
+            .filter(|dir| dir.on_face(x, y, z, &config))
+            .filter(|&dir| !pipe_path::connected(&positions, (x, y, z), dir));
+        for dir in open {
+            let position = config.cell_position((x, y, z)) + dir.offset() * (0.5 - depth);
+            commands.spawn((
+                Mesh3d(assets.mesh.clone()),
+                MeshMaterial3d(assets.material.clone()),
+                Transform::from_translation(position).with_rotation(dir.pipe_rotation()),
+                PipeCap,
+            ));
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Colors cells by how many pipe segments pass through them, with a
+//! histogram of the distribution in the HUD, to spot congested cells.
+
+use bevy::prelude::*;
+
+use crate::{
+    Positions,
+    hud::{HudRoot, hud_line, spawn_hud_root},
+    keymap::{Action, Keymap},
+    overlays::BlockMaterialSet,
+};
+
+const HISTOGRAM_WIDTH: usize = 20;
+
+pub struct PipeCountsPlugin;
+
+impl Plugin for PipeCountsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_pipe_counts.after(spawn_hud_root))
+            .add_systems(
+                Update,
+                (toggle_pipe_counts_system, show_pipe_counts_system)
+                    .chain()
+                    .before(BlockMaterialSet::Overlays),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct PipeCounts {
+    enabled: bool,
+    /// Material for each segment count, indexed by count.
+    materials: Vec<Handle<StandardMaterial>>,
+}
+
+impl PipeCounts {
+    /// Material of the cell's segment count, replacing its color while
+    /// shown.
+    pub fn material(
+        &self,
+        positions: &Positions,
+        cell: (usize, usize, usize),
+    ) -> Option<&Handle<StandardMaterial>> {
+        // A watched solution can outgrow the counts seen at startup.
+        let count = segment_count(positions, cell).min(self.materials.len() - 1);
+        self.enabled.then(|| &self.materials[count])
+    }
+}
+
+#[derive(Component)]
+struct PipeCountsText;
+
+fn segment_count(positions: &Positions, cell: (usize, usize, usize)) -> usize {
+    positions.pipe_positions.get(&cell).map_or(0, Vec::len)
+}
+
+fn setup_pipe_counts(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    positions: Res<Positions>,
+    root: Single<Entity, With<HudRoot>>,
+) {
+    let mut histogram: Vec<usize> = Vec::new();
+    for &cell in positions.positions.keys() {
+        let count = segment_count(&positions, cell);
+        if histogram.len() <= count {
+            histogram.resize(count + 1, 0);
+        }
+        histogram[count] += 1;
+    }
+    let max = histogram.len().saturating_sub(1);
+
+    // Empty cells are grey, the rest go from blue (one segment) to red (max).
+    let count_materials = (0..=max)
+        .map(|count| {
+            let base_color = if count == 0 {
+                Color::srgb(0.3, 0.3, 0.3)
+            } else {
+                let t = (count - 1) as f32 / (max - 1).max(1) as f32;
+                Color::hsl(240.0 * (1.0 - t), 0.9, 0.5)
+            };
+            materials.add(StandardMaterial {
+                base_color,
+                ..default()
+            })
+        })
+        .collect();
+
+    let largest = histogram.iter().copied().max().unwrap_or(0).max(1);
+    let mut summary = format!("pipe segments per cell (max {max})");
+    for (count, cells) in histogram.iter().enumerate() {
+        let bar = "#".repeat((cells * HISTOGRAM_WIDTH).div_ceil(largest));
+        summary.push_str(&format!("\n{count:>2} | {bar} {cells}"));
+    }
+
+    commands.insert_resource(PipeCounts {
+        enabled: false,
+        materials: count_materials,
+    });
+    commands
+        .entity(*root)
+        .with_child((hud_line(summary, false), PipeCountsText));
+}
+
+fn toggle_pipe_counts_system(
+    key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut pipe_counts: ResMut<PipeCounts>,
+) {
+    if keymap.just_pressed(Action::PipeCounts, &key_input) {
+        pipe_counts.enabled = !pipe_counts.enabled;
+    }
+}
+
+fn show_pipe_counts_system(
+    pipe_counts: Res<PipeCounts>,
+    mut text_query: Query<&mut Node, With<PipeCountsText>>,
+) {
+    if !pipe_counts.is_changed() {
+        return;
+    }
+
+    for mut node in text_query.iter_mut() {
+        node.display = if pipe_counts.enabled {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
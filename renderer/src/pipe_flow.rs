@@ -0,0 +1,116 @@
+//! `--pipe-flow <file>`: thickens every pipe piece by a per-segment value
+//! from a simulation (a flow rate, say), so busy segments read fatter than
+//! quiet ones. Values are spread between half and twice the usual
+//! thickness across the range of the file; pieces without one keep the
+//! usual thickness.
+//!
+//! The file has one `x,y,z direction value` line per piece, the direction
+//! being the side of the cell it leaves through, spelled like in the
+//! solution (`a`, `b`, `n`, `s`, `e` or `w`). A span between two cells
+//! takes the larger value of the two sides it joins. Blank lines and lines
+//! starting with `#` are ignored.
+
+use std::{collections::HashMap, fmt, fs, io, path::Path};
+
+use bevy::prelude::*;
+
+use crate::{Direction, parse_cell};
+
+type Cell = (usize, usize, usize);
+
+const MIN_THICKNESS: f32 = 0.5;
+const MAX_THICKNESS: f32 = 2.0;
+
+#[derive(Debug)]
+pub struct PipeFlow {
+    values: HashMap<(Cell, Direction), f32>,
+    min: f32,
+    max: f32,
+}
+
+impl PipeFlow {
+    pub fn load(path: &Path) -> Result<PipeFlow, PipeFlowError> {
+        let contents = fs::read_to_string(path).map_err(PipeFlowError::Io)?;
+        PipeFlow::parse(&contents)
+    }
+
+    fn parse(contents: &str) -> Result<PipeFlow, PipeFlowError> {
+        let mut values = HashMap::new();
+
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let malformed = || PipeFlowError::Malformed { line: index + 1 };
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [cell, direction, value] = fields[..] else {
+                return Err(malformed());
+            };
+            let cell = parse_cell(cell).map_err(|_| malformed())?;
+            let direction = direction.parse::<Direction>().map_err(|()| malformed())?;
+            let value = value
+                .parse::<f32>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .ok_or_else(malformed)?;
+            values.insert((cell, direction), value);
+        }
+
+        let min = values.values().copied().fold(f32::INFINITY, f32::min);
+        let max = values.values().copied().fold(f32::NEG_INFINITY, f32::max);
+        Ok(PipeFlow { values, min, max })
+    }
+
+    /// Thickness of the piece leaving `cell` through `direction`, relative
+    /// to the usual one.
+    pub fn thickness(&self, cell: Cell, direction: Direction) -> f32 {
+        let Some(&value) = self.values.get(&(cell, direction)) else {
+            return 1.0;
+        };
+        let range = self.max - self.min;
+        let t = if range > 0.0 { (value - self.min) / range } else { 0.5 };
+        MIN_THICKNESS + t * (MAX_THICKNESS - MIN_THICKNESS)
+    }
+
+    /// Thickness of the span from `cell` to its neighbor through
+    /// `direction`, the larger of its two ends that have a value.
+    pub fn span_thickness(&self, (x, y, z): Cell, direction: Direction) -> f32 {
+        let neighbor = (IVec3::new(x as i32, y as i32, z as i32) + direction.offset().as_ivec3())
+            .to_array()
+            .map(|coord| coord as usize);
+        let ends = [
+            ((x, y, z), direction),
+            ((neighbor[0], neighbor[1], neighbor[2]), direction.opposite()),
+        ];
+        ends.into_iter()
+            .filter(|end| self.values.contains_key(end))
+            .map(|(cell, direction)| self.thickness(cell, direction))
+            .reduce(f32::max)
+            .unwrap_or(1.0)
+    }
+}
+
+/// Scale thickening an unrotated piece running along `direction` by
+/// `thickness`, leaving its length alone.
+pub fn across(direction: Direction, thickness: f32) -> Vec3 {
+    Vec3::splat(thickness) + (1.0 - thickness) * direction.offset().abs()
+}
+
+#[derive(Debug)]
+pub enum PipeFlowError {
+    Io(io::Error),
+    Malformed { line: usize },
+}
+
+impl fmt::Display for PipeFlowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PipeFlowError::Io(err) => write!(f, "{err}"),
+            PipeFlowError::Malformed { line } => write!(f, "line {line}: expected `x,y,z direction value`"),
+        }
+    }
+}
+
+impl std::error::Error for PipeFlowError {}
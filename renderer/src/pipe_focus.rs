@@ -0,0 +1,77 @@
+//! Pipe focus: hides the solid blocks and outlines every filled cell as a
+//! faint wireframe instead, so the pipe routing stands out while the box
+//! structure stays visible. Toggled with F, or on from the start with
+//! `--pipe-focus`.
+
+use bevy::prelude::*;
+
+use crate::{
+    Block, Config, Positions,
+    instancing::InstancedBlocks,
+    keymap::{Action, Keymap},
+    switch_layer_system,
+};
+
+pub struct PipeFocusPlugin;
+
+impl Plugin for PipeFocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                toggle_pipe_focus_system,
+                // The layer toggle rewrites block visibility every frame.
+                hide_blocks_system.after(switch_layer_system),
+                draw_wireframes_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn toggle_pipe_focus_system(
+    key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut config: ResMut<Config>,
+) {
+    if keymap.just_pressed(Action::PipeFocus, &key_input) {
+        config.pipe_focus = !config.pipe_focus;
+    }
+}
+
+fn hide_blocks_system(
+    config: Res<Config>,
+    mut instanced: Query<&mut Visibility, (With<InstancedBlocks>, Without<Block>)>,
+    mut blocks: Query<&mut Visibility, With<Block>>,
+) {
+    if config.pipe_focus {
+        for mut visibility in blocks.iter_mut() {
+            *visibility = Visibility::Hidden;
+        }
+    }
+    if config.is_changed() {
+        for mut visibility in instanced.iter_mut() {
+            *visibility = if config.pipe_focus {
+                Visibility::Hidden
+            } else {
+                Visibility::Visible
+            };
+        }
+    }
+}
+
+fn draw_wireframes_system(config: Res<Config>, positions: Res<Positions>, mut gizmos: Gizmos) {
+    if !config.pipe_focus {
+        return;
+    }
+
+    for &(x, y, z) in positions.positions.keys() {
+        if config.face.is_some_and(|face| !face.on_face(x, y, z, &config)) {
+            continue;
+        }
+        gizmos.cuboid(
+            Transform::from_translation(config.cell_position((x, y, z))),
+            config.wireframe_color,
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! `--pipe-quality high`: each pipe segment becomes one mesh whose two
+//! arms meet in a clean 45° miter at the cell center, instead of two
+//! overlapping cuboids.
+//!
+//! An arm runs from the face of the cell to the plane bisecting the two
+//! directions of the segment; for a straight segment that's the plane
+//! through the center, so both arms together make one bar.
+
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+};
+
+use crate::Direction;
+
+/// Half the side of the square cross-section, matching the stub cuboids.
+const HALF_WIDTH: f32 = 0.15;
+
+/// Mesh of the segment joining `in_dir` and `out_dir`, centered on the
+/// cell.
+pub fn segment_mesh(in_dir: Direction, out_dir: Direction) -> Mesh {
+    let mut builder = MeshBuilder::default();
+    builder.arm(in_dir, out_dir);
+    builder.arm(out_dir, in_dir);
+
+    Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, builder.positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, builder.normals)
+        .with_inserted_indices(Indices::U32(builder.indices))
+}
+
+#[derive(Default)]
+struct MeshBuilder {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    indices: Vec<u32>,
+}
+
+impl MeshBuilder {
+    /// The arm along `direction`, cut where it meets the arm along
+    /// `other`.
+    fn arm(&mut self, direction: Direction, other: Direction) {
+        let axis = direction.offset();
+        // The side of the cross-section facing `other`; any perpendicular
+        // for a straight segment, whose cut doesn't depend on it.
+        let bend = if other == direction.opposite() {
+            axis.any_orthonormal_vector()
+        } else {
+            other.offset()
+        };
+        let side = axis.cross(bend);
+
+        // Where the corner at `along_bend` on the cross-section meets the
+        // miter plane.
+        let straight = other == direction.opposite();
+        let cut = |along_bend: f32| if straight { 0.0 } else { along_bend };
+        let corner = |along_bend: f32, along_side: f32, along_axis: f32| {
+            axis * along_axis + bend * along_bend + side * along_side
+        };
+
+        let h = HALF_WIDTH;
+        let far = 0.5;
+        // Faces towards and away from the bend.
+        for s in [h, -h] {
+            self.quad(
+                [
+                    corner(s, -h, cut(s)),
+                    corner(s, h, cut(s)),
+                    corner(s, h, far),
+                    corner(s, -h, far),
+                ],
+                bend * s.signum(),
+            );
+        }
+        // The two sides, trapezoids when the arm is mitered.
+        for r in [h, -h] {
+            self.quad(
+                [
+                    corner(-h, r, cut(-h)),
+                    corner(h, r, cut(h)),
+                    corner(h, r, far),
+                    corner(-h, r, far),
+                ],
+                side * r.signum(),
+            );
+        }
+        // The end flush with the face of the cell.
+        self.quad(
+            [
+                corner(-h, -h, far),
+                corner(h, -h, far),
+                corner(h, h, far),
+                corner(-h, h, far),
+            ],
+            axis,
+        );
+    }
+
+    /// A flat quad given its corners in order around the edge, either way
+    /// round; wound so it faces `normal`.
+    fn quad(&mut self, mut corners: [Vec3; 4], normal: Vec3) {
+        if (corners[1] - corners[0]).cross(corners[2] - corners[0]).dot(normal) < 0.0 {
+            corners.reverse();
+        }
+
+        let start = self.positions.len() as u32;
+        for corner in corners {
+            self.positions.push(corner.to_array());
+            self.normals.push(normal.to_array());
+        }
+        self.indices
+            .extend([start, start + 1, start + 2, start, start + 2, start + 3]);
+    }
+}
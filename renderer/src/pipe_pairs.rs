@@ -0,0 +1,63 @@
+//! `--pipe-colors pair`: gives every pipe shape (the pair of faces a
+//! segment joins, straight or bent) its own color, with a legend in the
+//! HUD, to check the mix of bends the generator produces.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+
+use crate::{
+    Direction, Positions,
+    hud::{HudRoot, hud_line, spawn_hud_root},
+};
+
+pub struct PipePairsPlugin;
+
+impl Plugin for PipePairsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_pipe_pairs_legend.after(spawn_hud_root));
+    }
+}
+
+/// The pair a segment joins, independent of which end is the inlet.
+pub fn shape(in_dir: Direction, out_dir: Direction) -> (Direction, Direction) {
+    (in_dir.min(out_dir), in_dir.max(out_dir))
+}
+
+/// Every shape occurring in the solution with its color and segment count,
+/// colors spread evenly around the hue wheel.
+pub fn shape_colors(positions: &Positions) -> BTreeMap<(Direction, Direction), (Color, usize)> {
+    let mut counts: BTreeMap<(Direction, Direction), usize> = BTreeMap::new();
+    for &(in_dir, out_dir) in positions.pipe_positions.values().flatten() {
+        *counts.entry(shape(in_dir, out_dir)).or_default() += 1;
+    }
+
+    let shapes = counts.len().max(1) as f32;
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(index, (shape, count))| {
+            (shape, (Color::hsl(360.0 * index as f32 / shapes, 0.8, 0.55), count))
+        })
+        .collect()
+}
+
+fn spawn_pipe_pairs_legend(
+    mut commands: Commands,
+    positions: Res<Positions>,
+    root: Option<Single<Entity, With<HudRoot>>>,
+) {
+    let Some(root) = root else {
+        return;
+    };
+
+    commands
+        .entity(*root)
+        .with_child(hud_line("pipe shapes", true))
+        .with_children(|parent| {
+            for ((a, b), (color, count)) in shape_colors(&positions) {
+                let kind = if a.opposite() == b { "straight" } else { "bend" };
+                parent.spawn((hud_line(format!("  {a}-{b} ({kind}): {count}"), true), TextColor(color)));
+            }
+        });
+}
@@ -0,0 +1,137 @@
+//! Orders the pipe segments into one route from end to end, for
+//! `--export-path`.
+//!
+//! Two cells are connected when each has a segment pointing at the
+//! other. A segment pointing at a cell that doesn't point back (usually
+//! out through a face of the box) is an open end of the route.
+
+use std::{collections::HashSet, fmt, fs, io, path::Path};
+
+use crate::{Direction, Positions};
+
+type Cell = (usize, usize, usize);
+
+/// One cell of the route with the side it is entered from and the side it
+/// is left through.
+pub struct Step {
+    pub cell: Cell,
+    pub from: Direction,
+    pub to: Direction,
+}
+
+#[derive(Debug)]
+pub enum PathError {
+    Io(io::Error),
+    NoPipes,
+    /// A cell holding more than one segment, where the route would cross
+    /// or branch.
+    Branch { cell: Cell },
+    /// The route must have exactly two open ends.
+    Ends { count: usize },
+    /// Segments left over after walking from one end to the other.
+    Disconnected { unvisited: usize },
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathError::Io(err) => write!(f, "{err}"),
+            PathError::NoPipes => write!(f, "the solution has no pipes"),
+            PathError::Branch { cell: (x, y, z) } => {
+                write!(f, "cell ({x},{y},{z}) holds more than one pipe segment")
+            }
+            PathError::Ends { count } => {
+                write!(f, "the pipes have {count} open ends instead of 2")
+            }
+            PathError::Disconnected { unvisited } => {
+                write!(f, "{unvisited} pipe cells are not on the route between the two ends")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+fn neighbor((x, y, z): Cell, direction: Direction) -> Option<Cell> {
+    let offset = direction.offset().as_ivec3();
+    Some((
+        x.checked_add_signed(offset.x as isize)?,
+        y.checked_add_signed(offset.y as isize)?,
+        z.checked_add_signed(offset.z as isize)?,
+    ))
+}
+
+/// The single segment of `cell`, if it has pipes at all.
+fn segment(positions: &Positions, cell: Cell) -> Option<(Direction, Direction)> {
+    positions.pipe_positions.get(&cell)?.first().copied()
+}
+
+/// Whether the neighbor of `cell` on `direction`'s side has a segment
+/// pointing back.
+pub fn connected(positions: &Positions, cell: Cell, direction: Direction) -> bool {
+    neighbor(cell, direction)
+        .and_then(|next| segment(positions, next))
+        .is_some_and(|(a, b)| a == direction.opposite() || b == direction.opposite())
+}
+
+/// The route from the open end in the lowest cell to the other one.
+pub fn ordered_path(positions: &Positions) -> Result<Vec<Step>, PathError> {
+    let mut cells: Vec<Cell> = Vec::new();
+    for (&cell, segments) in &positions.pipe_positions {
+        match segments.len() {
+            0 => {}
+            1 => cells.push(cell),
+            _ => return Err(PathError::Branch { cell }),
+        }
+    }
+    if cells.is_empty() {
+        return Err(PathError::NoPipes);
+    }
+    cells.sort();
+
+    let mut ends = Vec::new();
+    for &cell in &cells {
+        let (a, b) = segment(positions, cell).unwrap();
+        for (open, other) in [(a, b), (b, a)] {
+            if !connected(positions, cell, open) {
+                ends.push((cell, open, other));
+            }
+        }
+    }
+    let &[(start, mut from, mut to), _] = ends.as_slice() else {
+        return Err(PathError::Ends { count: ends.len() });
+    };
+
+    let mut visited = HashSet::new();
+    let mut steps = Vec::new();
+    let mut cell = start;
+    loop {
+        visited.insert(cell);
+        steps.push(Step { cell, from, to });
+
+        if !connected(positions, cell, to) {
+            break;
+        }
+        cell = neighbor(cell, to).unwrap();
+        // Two ends and a segment per cell leave no room for a cycle.
+        from = to.opposite();
+        let (a, b) = segment(positions, cell).unwrap();
+        to = if a == from { b } else { a };
+    }
+
+    if visited.len() != cells.len() {
+        return Err(PathError::Disconnected {
+            unvisited: cells.len() - visited.len(),
+        });
+    }
+    Ok(steps)
+}
+
+/// Writes the route to `path`, one `x,y,z from -> to` line per cell.
+pub fn export(path: &Path, positions: &Positions) -> Result<(), PathError> {
+    let lines: String = ordered_path(positions)?
+        .iter()
+        .map(|Step { cell: (x, y, z), from, to }| format!("{x},{y},{z} {from} -> {to}\n"))
+        .collect();
+    fs::write(path, lines).map_err(PathError::Io)
+}
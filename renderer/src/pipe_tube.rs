@@ -0,0 +1,75 @@
+//! Pipe tube: hides every block the pipe doesn't pass through, leaving the
+//! pipe and the cells it threads, like a tube carved out of the box. With
+//! `--tube-context` the hidden blocks are outlined faintly so the tube can
+//! still be placed in the box. Toggled with K, or on from the start with
+//! `--pipe-tube`. Instanced blocks are drawn all at once and stay as they
+//! are.
+
+use bevy::prelude::*;
+
+use crate::{
+    Block, Config, Positions,
+    keymap::{Action, Keymap},
+    switch_layer_system,
+};
+
+pub struct PipeTubePlugin;
+
+impl Plugin for PipeTubePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                toggle_pipe_tube_system,
+                // The layer toggle rewrites block visibility every frame.
+                hide_blocks_system.after(switch_layer_system),
+                draw_context_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn toggle_pipe_tube_system(
+    key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut config: ResMut<Config>,
+) {
+    if keymap.just_pressed(Action::PipeTube, &key_input) {
+        config.pipe_tube = !config.pipe_tube;
+    }
+}
+
+fn hide_blocks_system(
+    config: Res<Config>,
+    positions: Res<Positions>,
+    mut blocks: Query<(&Block, &mut Visibility)>,
+) {
+    if !config.pipe_tube {
+        return;
+    }
+
+    for (block, mut visibility) in blocks.iter_mut() {
+        if !positions.pipe_positions.contains_key(&(block.x, block.y, block.z)) {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+fn draw_context_system(config: Res<Config>, positions: Res<Positions>, mut gizmos: Gizmos) {
+    if !config.pipe_tube || !config.tube_context {
+        return;
+    }
+
+    for &(x, y, z) in positions.positions.keys() {
+        if positions.pipe_positions.contains_key(&(x, y, z))
+            || config.face.is_some_and(|face| !face.on_face(x, y, z, &config))
+        {
+            continue;
+        }
+        gizmos.cuboid(
+            Transform::from_translation(config.cell_position((x, y, z))),
+            config.wireframe_color,
+        );
+    }
+}
@@ -0,0 +1,283 @@
+//! `--playback <seconds>`: plays the models of a solver run as the frames
+//! of an animation, one every SECONDS, for outputs where each model is a
+//! step in time. Between two frames the blocks that change cross-fade:
+//! those only in the next frame fade in where they'll be, those leaving
+//! fade out. Space plays and pauses, comma and period scrub back and
+//! forward a quarter of a frame, and the HUD shows where the timeline is.
+//! Pipes switch at whole frames.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{
+    Block, BlockInfo, BlockMaterials, Config, Positions, SolutionEntities, id_color,
+    hud::{HudRoot, hud_line, spawn_hud_root},
+    keymap::{Action, Keymap},
+    overlays::BlockMaterialSet,
+    respawn_solution,
+};
+
+/// How far one scrub key press moves, in frames.
+const SCRUB_STEP: f32 = 0.25;
+/// Characters in the HUD timeline bar.
+const BAR_WIDTH: usize = 30;
+
+pub struct PlaybackPlugin {
+    /// Every model in the order the solver printed them.
+    pub models: Vec<Positions>,
+    pub seconds_per_frame: f32,
+}
+
+impl Plugin for PlaybackPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Timeline {
+            models: self.models.clone(),
+            position: 0.0,
+            seconds_per_frame: self.seconds_per_frame,
+            playing: true,
+            shown: None,
+        })
+        .init_resource::<FadeMaterials>()
+        .add_systems(Startup, spawn_timeline_text.after(spawn_hud_root))
+        .add_systems(
+            Update,
+            (
+                playback_keys_system,
+                advance_system,
+                show_frame_system,
+                crossfade_system.in_set(BlockMaterialSet::Swaps),
+                update_timeline_text_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct Timeline {
+    models: Vec<Positions>,
+    /// Frames since the first one; the fraction is how far the fade to the
+    /// next frame has got.
+    position: f32,
+    seconds_per_frame: f32,
+    playing: bool,
+    /// Frame whose blocks are spawned.
+    shown: Option<usize>,
+}
+
+impl Timeline {
+    fn last(&self) -> f32 {
+        (self.models.len() - 1) as f32
+    }
+
+    fn frame(&self) -> usize {
+        self.position.floor() as usize
+    }
+
+    fn fade(&self) -> f32 {
+        self.position.fract()
+    }
+}
+
+/// Each block's color with the current fade applied, going in and coming
+/// out, so every changing block of an id shares one material.
+#[derive(Resource, Default)]
+struct FadeMaterials {
+    fading_in: HashMap<String, Handle<StandardMaterial>>,
+    fading_out: HashMap<String, Handle<StandardMaterial>>,
+}
+
+/// A cell of block `id` in the next frame, fading in over the current one.
+#[derive(Component)]
+struct Incoming {
+    id: String,
+}
+
+#[derive(Component)]
+struct TimelineText;
+
+fn timeline_text(timeline: &Timeline) -> String {
+    let filled = (timeline.position / timeline.last().max(1.0) * BAR_WIDTH as f32).round() as usize;
+    format!(
+        "[{}{}] frame {:.2}/{} {}",
+        "=".repeat(filled),
+        "-".repeat(BAR_WIDTH - filled),
+        timeline.position + 1.0,
+        timeline.models.len(),
+        if timeline.playing { "playing" } else { "paused" },
+    )
+}
+
+fn spawn_timeline_text(mut commands: Commands, timeline: Res<Timeline>, root: Option<Single<Entity, With<HudRoot>>>) {
+    if let Some(root) = root {
+        commands
+            .entity(*root)
+            .with_child((hud_line(timeline_text(&timeline), true), TimelineText));
+    }
+}
+
+fn update_timeline_text_system(timeline: Res<Timeline>, mut text_query: Query<&mut Text, With<TimelineText>>) {
+    if timeline.is_changed() {
+        for mut text in text_query.iter_mut() {
+            text.0 = timeline_text(&timeline);
+        }
+    }
+}
+
+fn playback_keys_system(
+    key_input: Option<Res<ButtonInput<KeyCode>>>,
+    keymap: Res<Keymap>,
+    mut timeline: ResMut<Timeline>,
+) {
+    let Some(key_input) = key_input else {
+        return;
+    };
+
+    if keymap.just_pressed(Action::PlayPause, &key_input) {
+        // Playing from the end starts over.
+        if !timeline.playing && timeline.position >= timeline.last() {
+            timeline.position = 0.0;
+        }
+        timeline.playing = !timeline.playing;
+    }
+    let step = match (
+        keymap.just_pressed(Action::ScrubBack, &key_input),
+        keymap.just_pressed(Action::ScrubForward, &key_input),
+    ) {
+        (true, false) => -SCRUB_STEP,
+        (false, true) => SCRUB_STEP,
+        _ => return,
+    };
+    timeline.playing = false;
+    timeline.position = (timeline.position + step).clamp(0.0, timeline.last());
+}
+
+fn advance_system(time: Res<Time>, mut timeline: ResMut<Timeline>) {
+    if !timeline.playing {
+        return;
+    }
+    timeline.position += time.delta_secs() / timeline.seconds_per_frame;
+    if timeline.position >= timeline.last() {
+        timeline.position = timeline.last();
+        timeline.playing = false;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn show_frame_system(
+    mut commands: Commands,
+    mut timeline: ResMut<Timeline>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    config: Res<Config>,
+    block_materials: Res<BlockMaterials>,
+    spawned: Query<Entity, SolutionEntities>,
+    incoming: Query<Entity, With<Incoming>>,
+    // Made once and shared by the incoming cells of every frame.
+    mut cube: Local<Option<Handle<Mesh>>>,
+) {
+    let frame = timeline.frame();
+    if timeline.shown == Some(frame) {
+        return;
+    }
+    timeline.shown = Some(frame);
+
+    respawn_solution(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &spawned,
+        timeline.models[frame].clone(),
+        &config,
+        &block_materials,
+    );
+
+    for entity in incoming.iter() {
+        commands.entity(entity).despawn();
+    }
+    let Some(next) = timeline.models.get(frame + 1) else {
+        return;
+    };
+    let current = &timeline.models[frame];
+    let cube = cube.get_or_insert_with(|| meshes.add(Cuboid::new(1.0, 1.0, 1.0)));
+    for (&cell, BlockInfo { id, .. }) in &next.positions {
+        if current.block_id(&cell) == Some(id) {
+            continue;
+        }
+        // The material is set by the crossfade once it knows the fade.
+        commands.spawn((
+            Mesh3d(cube.clone()),
+            MeshMaterial3d::<StandardMaterial>::default(),
+            Transform::from_translation(config.cell_position(cell)),
+            Incoming { id: id.clone() },
+        ));
+    }
+}
+
+fn fade_material(
+    cache: &mut HashMap<String, Handle<StandardMaterial>>,
+    materials: &mut Assets<StandardMaterial>,
+    id: &str,
+    alpha: f32,
+    config: &Config,
+) -> Handle<StandardMaterial> {
+    let handle = cache
+        .entry(id.to_string())
+        .or_insert_with(|| {
+            materials.add(StandardMaterial {
+                base_color: id_color(id, config),
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            })
+        })
+        .clone();
+    if let Some(material) = materials.get_mut(&handle)
+        && material.base_color.alpha() != alpha
+    {
+        material.base_color.set_alpha(alpha);
+    }
+    handle
+}
+
+fn crossfade_system(
+    timeline: Res<Timeline>,
+    mut fades: ResMut<FadeMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    block_materials: Res<BlockMaterials>,
+    config: Res<Config>,
+    mut blocks: Query<(&Block, &mut MeshMaterial3d<StandardMaterial>), Without<Incoming>>,
+    mut incoming: Query<(&Incoming, &mut MeshMaterial3d<StandardMaterial>)>,
+) {
+    if !timeline.is_changed() {
+        return;
+    }
+
+    let frame = timeline.frame();
+    let fade = timeline.fade();
+    let current = &timeline.models[frame];
+    let Some(next) = timeline.models.get(frame + 1) else {
+        return;
+    };
+    let FadeMaterials { fading_in, fading_out } = &mut *fades;
+
+    for (block, mut material) in blocks.iter_mut() {
+        let cell = (block.x, block.y, block.z);
+        let Some(id) = current.block_id(&cell) else {
+            continue;
+        };
+        if next.block_id(&cell) == Some(id) {
+            continue;
+        }
+        let faded = fade_material(fading_out, &mut materials, id, 1.0 - fade, &config);
+        material.0 = if fade > 0.0 {
+            faded
+        } else {
+            block_materials.cell(id, cell).clone()
+        };
+    }
+
+    for (incoming, mut material) in incoming.iter_mut() {
+        material.0 = fade_material(fading_in, &mut materials, &incoming.id, fade, &config);
+    }
+}
@@ -0,0 +1,41 @@
+//! `--query X,Y,Z`: describes one cell of the solution on stdout, for
+//! scripts that need a single answer without rendering anything.
+
+use crate::{BlockInfo, Config, Positions};
+
+/// The block at `cell` and the pipe segments running through it, one per
+/// line, or an error if the cell lies outside the box.
+pub fn describe_cell(
+    positions: &Positions,
+    config: &Config,
+    (x, y, z): (usize, usize, usize),
+) -> Result<String, String> {
+    let in_range = (1..=config.width).contains(&x)
+        && (1..=config.height).contains(&y)
+        && (1..=config.depth).contains(&z);
+    if !in_range {
+        return Err(format!(
+            "cell ({x},{y},{z}) is outside the {}x{}x{} box (coordinates start at 1)",
+            config.width, config.height, config.depth
+        ));
+    }
+
+    let mut description = match positions.positions.get(&(x, y, z)) {
+        Some(BlockInfo { id, unit, .. }) => format!("cell ({x},{y},{z}): block {id}, unit {unit}"),
+        None if positions.empty.contains(&(x, y, z)) => {
+            format!("cell ({x},{y},{z}): empty (marked by the solver)")
+        }
+        None => format!("cell ({x},{y},{z}): empty"),
+    };
+
+    match positions.pipe_positions.get(&(x, y, z)) {
+        Some(segments) if !segments.is_empty() => {
+            for (in_dir, out_dir) in segments {
+                description.push_str(&format!("\npipe: {in_dir} -> {out_dir}"));
+            }
+        }
+        _ => description.push_str("\npipe: none"),
+    }
+
+    Ok(description)
+}
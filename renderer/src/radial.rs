@@ -0,0 +1,44 @@
+//! `--color-mode radial`: colors every cell by its distance from the
+//! center of the box through the scalar field colormap, so the packing
+//! reads as concentric shells from the core out to the corners.
+//! `--distance-metric` picks how the distance is measured.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{Config, camera, scalar_field::ScalarField};
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DistanceMetric {
+    /// Sum of the distances along each axis, in diamond-shaped shells
+    Manhattan,
+    /// Straight-line distance, in round shells
+    Euclidean,
+}
+
+impl DistanceMetric {
+    fn distance(&self, offset: Vec3) -> f32 {
+        match self {
+            DistanceMetric::Manhattan => offset.abs().element_sum(),
+            DistanceMetric::Euclidean => offset.length(),
+        }
+    }
+}
+
+/// Distance of every cell of the box from its center, recomputed from
+/// the current dimensions.
+pub fn distance_field(config: &Config, metric: DistanceMetric) -> ScalarField {
+    let center = camera::box_center(config);
+    let mut values = HashMap::new();
+    for x in 1..=config.width {
+        for y in 1..=config.height {
+            for z in 1..=config.depth {
+                let offset = config.cell_position((x, y, z)) - center;
+                values.insert((x, y, z), metric.distance(offset));
+            }
+        }
+    }
+    ScalarField::new(values)
+}
@@ -0,0 +1,216 @@
+//! Recovery from block materials and meshes removed while still in use,
+//! so recoloring, reloading and cycling solutions never draw with a
+//! handle to nothing.
+//!
+//! Block colors are made again from the id and the blocks are pointed
+//! back at them; block cubes get a fresh cube. Anything else left with a
+//! removed asset is logged and skipped, and the next respawn replaces it.
+
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+
+use crate::{
+    Block, BlockMaterials, Config, FALLBACK_COLOR, Positions, id_color, overlays::BlockMaterialSet, shaded,
+};
+
+pub struct RecoveryPlugin;
+
+impl Plugin for RecoveryPlugin {
+    fn build(&self, app: &mut App) {
+        // Last, after the overlays and anything swapping their materials
+        // out this frame.
+        app.add_systems(
+            Update,
+            (recover_materials_system, recover_meshes_system).after(BlockMaterialSet::Swaps),
+        );
+    }
+}
+
+fn recover_materials_system(
+    mut events: EventReader<AssetEvent<StandardMaterial>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    block_materials: Option<ResMut<BlockMaterials>>,
+    positions: Res<Positions>,
+    config: Res<Config>,
+    mut meshes: Query<(Entity, &mut MeshMaterial3d<StandardMaterial>, Option<&Block>)>,
+) {
+    let removed: HashSet<AssetId<StandardMaterial>> = events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Removed { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    if removed.is_empty() {
+        return;
+    }
+    let Some(mut block_materials) = block_materials else {
+        return;
+    };
+
+    let BlockMaterials {
+        by_id,
+        striped,
+        by_cell,
+        fallback,
+    } = &mut *block_materials;
+    for (id, handle) in by_id.iter_mut() {
+        if removed.contains(&handle.id()) {
+            warn!("Material of block {id} was removed, making it again");
+            *handle = materials.add(id_color(id, &config));
+        }
+    }
+    for (id, handle) in striped.iter_mut() {
+        if removed.contains(&handle.id()) {
+            let strength = config.layer_stripe.unwrap_or_default();
+            *handle = materials.add(id_color(id, &config).darker(strength));
+        }
+    }
+    // Those cells fall back to their block's color.
+    by_cell.retain(|_, handle| !removed.contains(&handle.id()));
+    if removed.contains(&fallback.id()) {
+        *fallback = materials.add(FALLBACK_COLOR);
+    }
+
+    for (entity, mut material, block) in meshes.iter_mut() {
+        if !removed.contains(&material.id()) {
+            continue;
+        }
+        let Some(block) = block else {
+            warn!("Material of {entity} was removed, skipping it");
+            continue;
+        };
+        let cell = (block.x, block.y, block.z);
+        if let Some(id) = positions.block_id(&cell) {
+            material.0 = block_materials.cell(id, cell).clone();
+        }
+    }
+}
+
+fn recover_meshes_system(
+    mut events: EventReader<AssetEvent<Mesh>>,
+    mut assets: ResMut<Assets<Mesh>>,
+    config: Res<Config>,
+    mut meshes: Query<(Entity, &mut Mesh3d, Has<Block>)>,
+) {
+    let removed: HashSet<AssetId<Mesh>> = events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Removed { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    if removed.is_empty() {
+        return;
+    }
+
+    // Shared by every block that lost its cube.
+    let mut cube = None;
+    for (entity, mut mesh, is_block) in meshes.iter_mut() {
+        if !removed.contains(&mesh.id()) {
+            continue;
+        }
+        if is_block {
+            mesh.0 = cube
+                .get_or_insert_with(|| assets.add(shaded(Cuboid::new(1.0, 1.0, 1.0), &config)))
+                .clone();
+        } else {
+            warn!("Mesh of {entity} was removed, skipping it");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::AssetPlugin;
+
+    use super::*;
+    use crate::{ColorMode, DistanceMetric, PipeColors, PipeQuality, PipeShape, PipeStyle, parse_sol};
+
+    fn config() -> Config {
+        Config {
+            width: 1,
+            height: 1,
+            depth: 1,
+            origin: Vec3::ZERO,
+            hide: false,
+            hide_pipes: false,
+            pipes: true,
+            face: None,
+            flat_shading: false,
+            msaa: Msaa::Off,
+            scale_reference: false,
+            floor: false,
+            jitter_rotation: None,
+            seed: Some(0),
+            color_buckets: 12,
+            color_mode: ColorMode::Id,
+            distance_metric: DistanceMetric::Manhattan,
+            help_controls: false,
+            shape_stats: false,
+            instanced: false,
+            pipe_colors: PipeColors::Uniform,
+            pipe_style: PipeStyle::Stub,
+            pipe_shape: PipeShape::Cuboid,
+            pipe_radius: 0.15,
+            pipe_quality: PipeQuality::Low,
+            pipe_flow: None,
+            pipe_focus: false,
+            pipe_tube: false,
+            tube_context: false,
+            wireframe_color: Color::WHITE,
+            axis_colors: [Color::WHITE; 3],
+            scalar_field: None,
+            layer_stripe: None,
+            fov: 45f32.to_radians(),
+        }
+    }
+
+    #[test]
+    fn removed_block_assets_are_made_again() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), RecoveryPlugin))
+            .init_asset::<StandardMaterial>()
+            .init_asset::<Mesh>()
+            .insert_resource(config())
+            .insert_resource(parse_sol("block_pos(1,1,1,a,1)").unwrap());
+
+        let material = app
+            .world_mut()
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(Color::WHITE);
+        let fallback = app
+            .world_mut()
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(FALLBACK_COLOR);
+        let cube = app.world_mut().resource_mut::<Assets<Mesh>>().add(Cuboid::default());
+        app.insert_resource(BlockMaterials {
+            by_id: [("a".to_string(), material.clone())].into(),
+            striped: default(),
+            by_cell: default(),
+            fallback,
+        });
+        let block = app
+            .world_mut()
+            .spawn((Block { x: 1, y: 1, z: 1 }, Mesh3d(cube), MeshMaterial3d(material)))
+            .id();
+        app.update();
+
+        // As a reload or recolor would, several frames in a row.
+        for _ in 0..5 {
+            let material = app.world().get::<MeshMaterial3d<StandardMaterial>>(block).unwrap().id();
+            let mesh = app.world().get::<Mesh3d>(block).unwrap().id();
+            app.world_mut().resource_mut::<Assets<StandardMaterial>>().remove(material);
+            app.world_mut().resource_mut::<Assets<Mesh>>().remove(mesh);
+            app.update();
+            app.update();
+
+            let material = app.world().get::<MeshMaterial3d<StandardMaterial>>(block).unwrap();
+            assert!(app.world().resource::<Assets<StandardMaterial>>().contains(material));
+            assert_eq!(material.0, app.world().resource::<BlockMaterials>().by_id["a"]);
+            let mesh = app.world().get::<Mesh3d>(block).unwrap();
+            assert!(app.world().resource::<Assets<Mesh>>().contains(mesh));
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! `--scalar-field <file>`: colors every cell by a per-cell value from the
+//! solver (a cost, say) through a colormap instead of by block id, with
+//! the range of the field in the HUD. Cells without a value are grey.
+//!
+//! The file has one `x,y,z value` pair per line. Blank lines and lines
+//! starting with `#` are ignored.
+
+use std::{collections::HashMap, fmt, fs, io, path::Path};
+
+use bevy::prelude::*;
+
+use crate::{
+    Config,
+    hud::{HudRoot, hud_line, spawn_hud_root},
+    parse_cell,
+};
+
+/// Colormap steps; cells whose values fall in the same step share a
+/// material.
+pub const STEPS: usize = 64;
+pub const NEUTRAL_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+
+/// Viridis sampled at even intervals, interpolated in between.
+const VIRIDIS: [Color; 5] = [
+    Color::srgb(0.267, 0.005, 0.329),
+    Color::srgb(0.230, 0.322, 0.546),
+    Color::srgb(0.128, 0.567, 0.551),
+    Color::srgb(0.369, 0.789, 0.383),
+    Color::srgb(0.993, 0.906, 0.144),
+];
+
+pub struct ScalarFieldPlugin;
+
+impl Plugin for ScalarFieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_scalar_field_range.after(spawn_hud_root));
+    }
+}
+
+#[derive(Debug)]
+pub struct ScalarField {
+    values: HashMap<(usize, usize, usize), f32>,
+    min: f32,
+    max: f32,
+}
+
+impl ScalarField {
+    pub fn load(path: &Path) -> Result<ScalarField, ScalarFieldError> {
+        let contents = fs::read_to_string(path).map_err(ScalarFieldError::Io)?;
+        ScalarField::parse(&contents)
+    }
+
+    fn parse(contents: &str) -> Result<ScalarField, ScalarFieldError> {
+        let mut values = HashMap::new();
+
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let malformed = || ScalarFieldError::Malformed { line: index + 1 };
+            let (cell, value) = line.split_once(char::is_whitespace).ok_or_else(malformed)?;
+            let cell = parse_cell(cell).map_err(|_| malformed())?;
+            let value = value
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .ok_or_else(malformed)?;
+            values.insert(cell, value);
+        }
+
+        Ok(ScalarField::new(values))
+    }
+
+    pub fn new(values: HashMap<(usize, usize, usize), f32>) -> ScalarField {
+        let min = values.values().copied().fold(f32::INFINITY, f32::min);
+        let max = values.values().copied().fold(f32::NEG_INFINITY, f32::max);
+        ScalarField { values, min, max }
+    }
+
+    /// Colormap step of the value at `cell`, if it has one.
+    pub fn step(&self, cell: (usize, usize, usize)) -> Option<usize> {
+        let value = *self.values.get(&cell)?;
+        let range = self.max - self.min;
+        let t = if range > 0.0 { (value - self.min) / range } else { 0.5 };
+        Some(((t * STEPS as f32) as usize).min(STEPS - 1))
+    }
+}
+
+/// Color of colormap step `step`.
+pub fn step_color(step: usize) -> Color {
+    let t = (step as f32 + 0.5) / STEPS as f32 * (VIRIDIS.len() - 1) as f32;
+    let low = (t as usize).min(VIRIDIS.len() - 2);
+    VIRIDIS[low].mix(&VIRIDIS[low + 1], t - low as f32)
+}
+
+#[derive(Debug)]
+pub enum ScalarFieldError {
+    Io(io::Error),
+    Malformed { line: usize },
+}
+
+impl fmt::Display for ScalarFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScalarFieldError::Io(err) => write!(f, "{err}"),
+            ScalarFieldError::Malformed { line } => write!(f, "line {line}: expected `x,y,z value`"),
+        }
+    }
+}
+
+impl std::error::Error for ScalarFieldError {}
+
+fn spawn_scalar_field_range(
+    mut commands: Commands,
+    config: Res<Config>,
+    root: Option<Single<Entity, With<HudRoot>>>,
+) {
+    let (Some(field), Some(root)) = (&config.scalar_field, root) else {
+        return;
+    };
+
+    let range = if field.values.is_empty() {
+        "scalar field: no values".to_string()
+    } else {
+        format!("scalar field: min {}, max {}", field.min, field.max)
+    };
+    commands.entity(*root).with_child(hud_line(range, true));
+}
@@ -0,0 +1,66 @@
+//! A ruler of alternating one-unit segments laid along the south edge of
+//! the box, to convey its absolute size.
+
+use bevy::prelude::*;
+
+use crate::{
+    Config,
+    keymap::{Action, Keymap},
+};
+
+const RULER_THICKNESS: f32 = 0.1;
+
+#[derive(Component)]
+pub struct ScaleReference;
+
+pub fn spawn_scale_reference(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    config: Res<Config>,
+) {
+    let segment = meshes.add(Cuboid::new(1.0, RULER_THICKNESS, RULER_THICKNESS));
+    let colors = [
+        materials.add(StandardMaterial {
+            base_color: Color::BLACK,
+            ..default()
+        }),
+        materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            ..default()
+        }),
+    ];
+    let visibility = if config.scale_reference {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+
+    // Just outside the box: cells span 0.5..=n+0.5 on every axis.
+    let y = 0.5 + RULER_THICKNESS / 2.0;
+    let z = 0.5 - RULER_THICKNESS;
+    for x in 1..=config.width {
+        commands.spawn((
+            Mesh3d(segment.clone()),
+            MeshMaterial3d(colors[x % 2].clone()),
+            Transform::from_translation(config.origin + Vec3::new(x as f32, y, z)),
+            visibility,
+            ScaleReference,
+        ));
+    }
+}
+
+pub fn toggle_scale_reference_system(
+    key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut query: Query<&mut Visibility, With<ScaleReference>>,
+) {
+    if keymap.just_pressed(Action::ScaleReference, &key_input) {
+        for mut visibility in query.iter_mut() {
+            *visibility = match *visibility {
+                Visibility::Hidden => Visibility::Visible,
+                _ => Visibility::Hidden,
+            };
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! F12 saves the window as a PNG in the working directory, named after the
+//! box, the active layer and the time, e.g.
+//! `screenshot_3x3x3_layer2_1760443200123.png`.
+
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+    prelude::*,
+    render::view::screenshot::{Screenshot, ScreenshotCaptured, save_to_disk},
+};
+
+use crate::{
+    ActiveLayer, Config,
+    keymap::{Action, Keymap},
+};
+
+pub struct ScreenshotPlugin;
+
+impl Plugin for ScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, screenshot_system);
+    }
+}
+
+fn screenshot_path(config: &Config, layer: usize) -> PathBuf {
+    // Milliseconds, so shots taken in quick succession don't overwrite
+    // each other.
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis());
+    PathBuf::from(format!(
+        "screenshot_{}x{}x{}_layer{layer}_{millis}.png",
+        config.width, config.height, config.depth
+    ))
+}
+
+fn screenshot_system(
+    mut commands: Commands,
+    key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    config: Res<Config>,
+    active_layer: Res<ActiveLayer>,
+) {
+    if !keymap.just_pressed(Action::Screenshot, &key_input) {
+        return;
+    }
+
+    let path = screenshot_path(&config, active_layer.y);
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(path.clone()))
+        .observe(move |_trigger: Trigger<ScreenshotCaptured>| {
+            info!("Saved screenshot to {}", path.display());
+        });
+}
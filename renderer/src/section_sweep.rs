@@ -0,0 +1,162 @@
+//! `--export-section-sweep <dir>`: moves a cutting plane through the box
+//! along one axis and saves a numbered PNG per step, a CT-scan-like stack
+//! of the interior.
+//!
+//! Step `i` of `n` keeps the cells whose coordinate along the axis lies
+//! in the first `i / n` of the box, so the last frame shows it whole. The
+//! camera looks at the box from the positive side of every axis and never
+//! moves, so the frames line up. Instanced blocks are a single entity and
+//! are not cut.
+
+use std::{f32::consts::FRAC_PI_4, path::PathBuf};
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        renderer::RenderAdapter,
+        view::screenshot::save_to_disk,
+    },
+};
+
+use crate::{
+    Block, Config, Pipe, camera,
+    offscreen::{self, FrameSequence, FrameStep, PipelinesReady},
+};
+
+const SWEEP_WIDTH: u32 = 1280;
+const SWEEP_HEIGHT: u32 = 720;
+
+/// Same view as the first turntable frame.
+const SWEEP_YAW: f32 = FRAC_PI_4;
+const SWEEP_PITCH: f32 = FRAC_PI_4;
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionAxis {
+    X,
+    Y,
+    Z,
+}
+
+impl SectionAxis {
+    fn coord(&self, (x, y, z): (usize, usize, usize)) -> usize {
+        match self {
+            SectionAxis::X => x,
+            SectionAxis::Y => y,
+            SectionAxis::Z => z,
+        }
+    }
+
+    fn extent(&self, config: &Config) -> usize {
+        match self {
+            SectionAxis::X => config.width,
+            SectionAxis::Y => config.height,
+            SectionAxis::Z => config.depth,
+        }
+    }
+}
+
+pub struct SectionSweepPlugin {
+    pub dir: PathBuf,
+    pub axis: SectionAxis,
+    /// Defaults to one step per layer along the axis.
+    pub steps: Option<u32>,
+}
+
+impl Plugin for SectionSweepPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SectionSweep {
+            dir: self.dir.clone(),
+            axis: self.axis,
+            steps: self.steps,
+        })
+        .add_systems(Startup, spawn_sweep_camera)
+        .add_systems(Update, capture_section_frames);
+    }
+}
+
+#[derive(Resource)]
+struct SectionSweep {
+    dir: PathBuf,
+    axis: SectionAxis,
+    steps: Option<u32>,
+}
+
+#[derive(Component)]
+struct SweepCamera {
+    target: Handle<Image>,
+}
+
+fn spawn_sweep_camera(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    sweep: Res<SectionSweep>,
+    config: Res<Config>,
+    adapter: Res<RenderAdapter>,
+) {
+    let target = offscreen::render_target(&mut images, SWEEP_WIDTH, SWEEP_HEIGHT);
+    let steps = sweep.steps.unwrap_or(sweep.axis.extent(&config) as u32);
+
+    commands.spawn((
+        Camera3d::default(),
+        Camera {
+            target: RenderTarget::Image(target.clone()),
+            ..default()
+        },
+        camera::orbit_transform(
+            camera::box_center(&config),
+            SWEEP_YAW,
+            SWEEP_PITCH,
+            camera::framing_distance(&config, config.fov),
+        ),
+        camera::projection(&config),
+        camera::supported_msaa(config.msaa, &adapter),
+        SweepCamera { target },
+        FrameSequence::new(steps),
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn capture_section_frames(
+    mut commands: Commands,
+    sweep: Res<SectionSweep>,
+    ready: Res<PipelinesReady>,
+    config: Res<Config>,
+    camera: Single<(Entity, &SweepCamera, &mut FrameSequence)>,
+    mut blocks: Query<(&Block, &mut Visibility), Without<Pipe>>,
+    mut pipes: Query<(&Pipe, &mut Visibility), Without<Block>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let (entity, sweep_camera, mut sequence) = camera.into_inner();
+    match sequence.step(&ready) {
+        FrameStep::Wait => (),
+        FrameStep::Pose(step) => {
+            // Cells span `coord - 0.5..coord + 0.5`; keep those whose center
+            // is on the near side of the plane.
+            let extent = sweep.axis.extent(&config);
+            let plane = 0.5 + extent as f32 * (step + 1) as f32 / sequence.frames() as f32;
+            let axis = sweep.axis;
+            let visibility = |cell| {
+                if (axis.coord(cell) as f32) < plane {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                }
+            };
+            for (block, mut block_visibility) in blocks.iter_mut() {
+                *block_visibility = visibility((block.x, block.y, block.z));
+            }
+            for (pipe, mut pipe_visibility) in pipes.iter_mut() {
+                *pipe_visibility = visibility((pipe.x, pipe.y, pipe.z));
+            }
+        }
+        FrameStep::Capture(step) => {
+            let path = sweep.dir.join(format!("section_{step:04}.png"));
+            offscreen::capture(&mut commands, entity, &sweep_camera.target).observe(save_to_disk(path));
+        }
+        FrameStep::Done => {
+            info!("Section sweep: wrote {} frames to {}", sequence.frames(), sweep.dir.display());
+            exit.send(AppExit::Success);
+        }
+    }
+}
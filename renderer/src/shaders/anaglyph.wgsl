@@ -0,0 +1,20 @@
+// Red/cyan anaglyph of the two eye views: the red channel carries the
+// brightness of the left eye's view, green and blue the right eye's
+// colors, so red/cyan glasses hand each eye its own image.
+
+#import bevy_ui::ui_vertex_output::UiVertexOutput
+
+@group(1) @binding(0) var left_texture: texture_2d<f32>;
+@group(1) @binding(1) var left_sampler: sampler;
+@group(1) @binding(2) var right_texture: texture_2d<f32>;
+@group(1) @binding(3) var right_sampler: sampler;
+
+// Rec. 709 luma weights.
+const LUMA: vec3<f32> = vec3<f32>(0.2126, 0.7152, 0.0722);
+
+@fragment
+fn fragment(in: UiVertexOutput) -> @location(0) vec4<f32> {
+    let left = textureSample(left_texture, left_sampler, in.uv).rgb;
+    let right = textureSample(right_texture, right_sampler, in.uv).rgb;
+    return vec4<f32>(dot(left, LUMA), right.g, right.b, 1.0);
+}
@@ -0,0 +1,36 @@
+// One cube mesh drawn once per block, offset, scaled and colored by the
+// per-instance attributes.
+
+#import bevy_pbr::view_transformations::position_world_to_clip
+
+struct Vertex {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(3) i_position_scale: vec4<f32>,
+    @location(4) i_color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+// Fixed light from above and to the side, so faces of a cube stay apart
+// without going through the full PBR lighting.
+const LIGHT_DIRECTION: vec3<f32> = vec3<f32>(0.3, 0.9, 0.4);
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    let position = vertex.position * vertex.i_position_scale.w + vertex.i_position_scale.xyz;
+    let light = 0.6 + 0.4 * max(dot(vertex.normal, normalize(LIGHT_DIRECTION)), 0.0);
+
+    var out: VertexOutput;
+    out.clip_position = position_world_to_clip(position);
+    out.color = vec4<f32>(vertex.i_color.rgb * light, vertex.i_color.a);
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
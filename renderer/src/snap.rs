@@ -0,0 +1,69 @@
+//! `--snap-angle <degrees>`: once an orbit drag is released, the camera
+//! settles on the nearest yaw and pitch that are multiples of the angle,
+//! so exact isometric-like views are easy to land on and to reproduce.
+//! The camera still follows the mouse freely while dragging. A toggles
+//! snapping.
+
+use bevy::prelude::*;
+use bevy_panorbit_camera::PanOrbitCamera;
+
+use crate::keymap::{Action, Keymap};
+
+pub struct SnapPlugin {
+    /// Increment in radians.
+    pub angle: f32,
+}
+
+impl Plugin for SnapPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Snap {
+            angle: self.angle,
+            enabled: true,
+        })
+        .add_systems(Update, (toggle_snap_system, snap_camera_system).chain());
+    }
+}
+
+#[derive(Resource)]
+struct Snap {
+    angle: f32,
+    enabled: bool,
+}
+
+pub fn parse_snap_angle(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(degrees) if degrees > 0.0 && degrees <= 180.0 => Ok(degrees),
+        _ => Err(format!("expected an angle above 0 and at most 180 degrees; got `{s}`")),
+    }
+}
+
+fn toggle_snap_system(key_input: Res<ButtonInput<KeyCode>>, keymap: Res<Keymap>, mut snap: ResMut<Snap>) {
+    if keymap.just_pressed(Action::SnapAngle, &key_input) {
+        snap.enabled = !snap.enabled;
+        info!("Camera angle snapping {}", if snap.enabled { "on" } else { "off" });
+    }
+}
+
+fn snap_camera_system(
+    snap: Res<Snap>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    mut pan_orbit_query: Query<&mut PanOrbitCamera>,
+) {
+    if !snap.enabled {
+        return;
+    }
+
+    let nearest = |angle: f32| (angle / snap.angle).round() * snap.angle;
+    for mut pan_orbit in pan_orbit_query.iter_mut() {
+        if mouse_input.pressed(pan_orbit.button_orbit) {
+            continue;
+        }
+        // Only write when it changes, so the camera isn't marked as moving
+        // every frame.
+        let (yaw, pitch) = (nearest(pan_orbit.target_yaw), nearest(pan_orbit.target_pitch));
+        if yaw != pan_orbit.target_yaw || pitch != pan_orbit.target_pitch {
+            pan_orbit.target_yaw = yaw;
+            pan_orbit.target_pitch = pitch;
+        }
+    }
+}
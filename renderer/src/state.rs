@@ -0,0 +1,250 @@
+//! Snapshots of the interactive state: with `--save-state <file>`, F5
+//! writes the camera, the active layer, the visibility toggles, the focus
+//! selection, the color mode and the model on screen to a JSON file, and
+//! `--load-state <file>` brings a session back to exactly that point. The
+//! counterpart of the command line for what was done after startup.
+//!
+//! Snapshots carry a format version. Settings a snapshot doesn't have keep
+//! what the command line says, so older snapshots still load; ones from a
+//! newer version are refused.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "camera": { "focus": [2.0, 2.0, 2.0], "yaw": 0.78, "pitch": 0.78, "radius": 9.1 },
+//!   "layer": 2, "sliced": true,
+//!   "focus": { "enabled": true, "center": [1, 2, 1], "radius": 2 },
+//!   "solution": 0
+//! }
+//! ```
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use bevy_panorbit_camera::PanOrbitCamera;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ActiveLayer, ColorMode, Config,
+    axes::Axes,
+    blink::Solutions,
+    focus::Focus,
+    footprint::Footprint,
+    keymap::{Action, Keymap},
+    pip::Pip,
+    radial::DistanceMetric,
+    transparent::Transparent,
+    wireframe::WireframeMode,
+};
+
+/// Format version written by this build.
+pub const VERSION: u32 = 1;
+
+type Cell = (usize, usize, usize);
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct State {
+    pub version: u32,
+    pub camera: Option<CameraState>,
+    pub layer: Option<usize>,
+    pub sliced: Option<bool>,
+    pub hide_blocks: Option<bool>,
+    pub hide_pipes: Option<bool>,
+    pub pipe_focus: Option<bool>,
+    pub pipe_tube: Option<bool>,
+    pub color_mode: Option<ColorMode>,
+    pub distance_metric: Option<DistanceMetric>,
+    pub footprint: Option<bool>,
+    pub axes: Option<bool>,
+    pub pip: Option<bool>,
+    pub wireframe: Option<bool>,
+    pub transparent: Option<bool>,
+    /// Opacity of the blocks in transparent mode.
+    pub opacity: Option<f32>,
+    pub focus: Option<FocusState>,
+    /// Index of the model on screen when the output has several.
+    pub solution: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct CameraState {
+    focus: [f32; 3],
+    yaw: f32,
+    pitch: f32,
+    radius: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct FocusState {
+    enabled: bool,
+    center: Option<Cell>,
+    radius: usize,
+}
+
+impl State {
+    pub fn load(path: &Path) -> Result<State, StateError> {
+        let contents = fs::read_to_string(path).map_err(StateError::Io)?;
+        let state: State = serde_json::from_str(&contents).map_err(StateError::Json)?;
+        if state.version > VERSION {
+            return Err(StateError::Version(state.version));
+        }
+        Ok(state)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), StateError> {
+        let contents = serde_json::to_string_pretty(self).map_err(StateError::Json)?;
+        fs::write(path, contents).map_err(StateError::Io)
+    }
+}
+
+/// Restores what still has to be set up once the app is running; the
+/// rest goes into `Config` and the plugins' settings in `main`.
+pub struct StatePlugin {
+    /// Where F5 writes snapshots.
+    pub save: Option<PathBuf>,
+    /// Format version of the loaded snapshot, if any.
+    pub version: Option<u32>,
+    pub camera: Option<CameraState>,
+    pub focus: Option<FocusState>,
+    pub wireframe: Option<bool>,
+}
+
+impl Plugin for StatePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Restore {
+            version: self.version,
+            camera: self.camera,
+            focus: self.focus,
+            wireframe: self.wireframe,
+        })
+        // After every camera has been spawned and framed at startup.
+        .add_systems(PostStartup, restore_state_system);
+
+        if let Some(path) = &self.save {
+            app.insert_resource(SavePath(path.clone()))
+                .add_systems(Update, save_state_system);
+        }
+    }
+}
+
+#[derive(Resource)]
+struct Restore {
+    version: Option<u32>,
+    camera: Option<CameraState>,
+    focus: Option<FocusState>,
+    wireframe: Option<bool>,
+}
+
+#[derive(Resource)]
+struct SavePath(PathBuf);
+
+fn restore_state_system(
+    restore: Res<Restore>,
+    mut cameras: Query<&mut PanOrbitCamera>,
+    focus: Option<ResMut<Focus>>,
+    wireframe: Option<ResMut<WireframeMode>>,
+) {
+    if let Some(version) = restore.version.filter(|&version| version < VERSION) {
+        warn!("The snapshot is format version {version}; settings it doesn't have keep their command line values");
+    }
+    if let Some(state) = restore.camera {
+        // Not initialized yet, so the plugin picks these up as the start
+        // position.
+        for mut camera in cameras.iter_mut() {
+            camera.focus = Vec3::from_array(state.focus);
+            camera.target_focus = camera.focus;
+            camera.yaw = Some(state.yaw);
+            camera.pitch = Some(state.pitch);
+            camera.radius = Some(state.radius);
+        }
+    }
+    if let (Some(state), Some(mut focus)) = (restore.focus, focus) {
+        focus.enabled = state.enabled;
+        focus.center = state.center;
+        focus.radius = state.radius;
+    }
+    if let (Some(shown), Some(mut wireframe)) = (restore.wireframe, wireframe) {
+        wireframe.shown = shown;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn save_state_system(
+    key_input: Option<Res<ButtonInput<KeyCode>>>,
+    keymap: Res<Keymap>,
+    path: Res<SavePath>,
+    config: Res<Config>,
+    active_layer: Res<ActiveLayer>,
+    camera: Option<Single<&PanOrbitCamera>>,
+    focus: Option<Res<Focus>>,
+    wireframe: Option<Res<WireframeMode>>,
+    footprint: Option<Res<Footprint>>,
+    axes: Option<Res<Axes>>,
+    pip: Option<Res<Pip>>,
+    transparent: Res<Transparent>,
+    solutions: Option<Res<Solutions>>,
+) {
+    if !key_input.is_some_and(|key_input| keymap.just_pressed(Action::SaveState, &key_input)) {
+        return;
+    }
+
+    let state = State {
+        version: VERSION,
+        camera: camera.map(|camera| CameraState {
+            focus: camera.target_focus.to_array(),
+            yaw: camera.target_yaw,
+            pitch: camera.target_pitch,
+            radius: camera.target_radius,
+        }),
+        layer: Some(active_layer.y),
+        sliced: Some(active_layer.sliced),
+        hide_blocks: Some(config.hide),
+        hide_pipes: Some(config.hide_pipes),
+        pipe_focus: Some(config.pipe_focus),
+        pipe_tube: Some(config.pipe_tube),
+        color_mode: Some(config.color_mode),
+        distance_metric: Some(config.distance_metric),
+        footprint: footprint.map(|footprint| footprint.shown),
+        axes: axes.map(|axes| axes.shown),
+        pip: pip.map(|pip| pip.shown),
+        wireframe: wireframe.map(|wireframe| wireframe.shown),
+        transparent: Some(transparent.shown),
+        opacity: Some(transparent.alpha),
+        focus: focus.map(|focus| FocusState {
+            enabled: focus.enabled,
+            center: focus.center,
+            radius: focus.radius,
+        }),
+        solution: solutions.map(|solutions| solutions.current),
+    };
+    match state.save(&path.0) {
+        Ok(()) => info!("Saved the state to {}", path.0.display()),
+        Err(err) => error!("Failed to save the state to {}: {err}", path.0.display()),
+    }
+}
+
+#[derive(Debug)]
+pub enum StateError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// Written by a newer build.
+    Version(u32),
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StateError::Io(err) => write!(f, "{err}"),
+            StateError::Json(err) => write!(f, "{err}"),
+            StateError::Version(version) => {
+                write!(f, "snapshot format version {version} is newer than this build's {VERSION}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
@@ -0,0 +1,182 @@
+//! `renderer stats <dir>`: loads every solution in a directory and prints
+//! per-solution and aggregate packing statistics, as a table or CSV.
+//!
+//! Bundles (`.json`) carry their own dimensions and binary solutions
+//! (`.bin`) are read as written by `--dump-bin`; anything else is parsed as
+//! solver output, keeping its last model, in the box given on the command
+//! line. Files that fail to load are skipped and listed at the end.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{BlockInfo, Direction, Positions, binary, bundle::Bundle, input, parse_sol, split_models};
+
+struct SolutionStats {
+    name: String,
+    fill_ratio: f32,
+    blocks: usize,
+    pipe_length: usize,
+    floating_blocks: usize,
+    bounding_volume: usize,
+    exposed_faces: usize,
+}
+
+/// Loads a solution and the dimensions of its box.
+fn load(path: &Path, dimensions: (usize, usize, usize)) -> Result<(Positions, (usize, usize, usize)), String> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => {
+            let bundle = Bundle::load(path).map_err(|err| err.to_string())?;
+            let positions = bundle.positions().map_err(|err| err.to_string())?;
+            Ok((positions, (bundle.width, bundle.height, bundle.depth)))
+        }
+        Some("bin") => Ok((binary::load(path).map_err(|err| err.to_string())?, dimensions)),
+        _ => {
+            let solution = input::read_solution_file(path).map_err(|err| err.to_string())?;
+            let last = *split_models(&solution).last().unwrap();
+            Ok((parse_sol(last).map_err(|err| err.to_string())?, dimensions))
+        }
+    }
+}
+
+/// Blocks with no cell on the floor or directly on top of another block.
+fn floating_blocks(positions: &Positions) -> usize {
+    let mut supported: HashSet<&String> = HashSet::new();
+    for (&(x, y, z), BlockInfo { id, .. }) in &positions.positions {
+        let below = positions.block_id(&(x, y.wrapping_sub(1), z));
+        if y == 1 || below.is_some_and(|below| below != id) {
+            supported.insert(id);
+        }
+    }
+
+    let ids: HashSet<&String> = positions.positions.values().map(|block| &block.id).collect();
+    ids.len() - supported.len()
+}
+
+/// Cells in the smallest box around every filled cell.
+pub fn bounding_volume(positions: &Positions) -> usize {
+    let mut cells = positions.positions.keys();
+    let Some(&first) = cells.next() else {
+        return 0;
+    };
+    let (min, max) = cells.fold((first, first), |(min, max), &(x, y, z)| {
+        (
+            (min.0.min(x), min.1.min(y), min.2.min(z)),
+            (max.0.max(x), max.1.max(y), max.2.max(z)),
+        )
+    });
+    (max.0 - min.0 + 1) * (max.1 - min.1 + 1) * (max.2 - min.2 + 1)
+}
+
+/// Faces of filled cells that don't touch another filled cell.
+pub fn exposed_faces(positions: &Positions) -> usize {
+    positions
+        .positions
+        .keys()
+        .map(|&(x, y, z)| {
+            Direction::all()
+                .filter(|dir| {
+                    let offset = dir.offset().as_ivec3();
+                    let neighbor = (
+                        x.wrapping_add_signed(offset.x as isize),
+                        y.wrapping_add_signed(offset.y as isize),
+                        z.wrapping_add_signed(offset.z as isize),
+                    );
+                    !positions.positions.contains_key(&neighbor)
+                })
+                .count()
+        })
+        .sum()
+}
+
+fn analyze(name: String, positions: &Positions, (width, height, depth): (usize, usize, usize)) -> SolutionStats {
+    let volume = (width * height * depth).max(1);
+    let blocks: HashMap<&String, usize> = positions.positions.values().fold(HashMap::new(), |mut blocks, block| {
+        *blocks.entry(&block.id).or_default() += 1;
+        blocks
+    });
+
+    SolutionStats {
+        name,
+        fill_ratio: positions.positions.len() as f32 / volume as f32,
+        blocks: blocks.len(),
+        pipe_length: positions.pipe_positions.values().filter(|segments| !segments.is_empty()).count(),
+        floating_blocks: floating_blocks(positions),
+        bounding_volume: bounding_volume(positions),
+        exposed_faces: exposed_faces(positions),
+    }
+}
+
+pub fn run(dir: &Path, dimensions: (usize, usize, usize), csv: bool) -> io::Result<()> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<_>>()?;
+    paths.retain(|path| path.is_file());
+    paths.sort();
+
+    let mut stats = Vec::new();
+    let mut skipped = Vec::new();
+    for path in &paths {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        match load(path, dimensions) {
+            Ok((positions, _)) if positions.positions.is_empty() => {
+                skipped.push(format!("{name}: no blocks"));
+            }
+            Ok((positions, dimensions)) => stats.push(analyze(name, &positions, dimensions)),
+            Err(err) => skipped.push(format!("{name}: {err}")),
+        }
+    }
+
+    if csv {
+        println!("file,fill_ratio,blocks,pipe_length,floating_blocks,bounding_volume,exposed_faces");
+        for s in &stats {
+            println!(
+                "{},{:.4},{},{},{},{},{}",
+                s.name, s.fill_ratio, s.blocks, s.pipe_length, s.floating_blocks, s.bounding_volume, s.exposed_faces
+            );
+        }
+    } else {
+        let width = stats.iter().map(|s| s.name.len()).max().unwrap_or(0).max(4);
+        println!(
+            "{:<width$}  {:>6}  {:>6}  {:>5}  {:>8}  {:>6}  {:>7}",
+            "file", "fill", "blocks", "pipe", "floating", "bbox", "exposed"
+        );
+        for s in &stats {
+            println!(
+                "{:<width$}  {:>5.1}%  {:>6}  {:>5}  {:>8}  {:>6}  {:>7}",
+                s.name,
+                s.fill_ratio * 100.0,
+                s.blocks,
+                s.pipe_length,
+                s.floating_blocks,
+                s.bounding_volume,
+                s.exposed_faces
+            );
+        }
+    }
+
+    // The summary goes to stderr so CSV output stays a clean table.
+    eprintln!();
+    eprintln!("{} solutions, {} skipped", stats.len(), skipped.len());
+    if !stats.is_empty() {
+        let average_fill = stats.iter().map(|s| s.fill_ratio).sum::<f32>() / stats.len() as f32;
+        let pipe_lengths = stats.iter().map(|s| s.pipe_length);
+        eprintln!("average fill: {:.1}%", average_fill * 100.0);
+        eprintln!(
+            "pipe length: min {}, max {}",
+            pipe_lengths.clone().min().unwrap(),
+            pipe_lengths.max().unwrap()
+        );
+        eprintln!(
+            "with floating blocks: {}",
+            stats.iter().filter(|s| s.floating_blocks > 0).count()
+        );
+    }
+    for skip in &skipped {
+        eprintln!("skipped {skip}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,129 @@
+//! Longest straight pipe run: the most cells in a row the route crosses
+//! without turning, a quick measure of how bendy a solution's pipe is. The
+//! HUD always shows its length; L outlines the run itself.
+
+use std::ops::Range;
+
+use bevy::prelude::*;
+
+use crate::{
+    Config, Positions,
+    hud::{HudRoot, hud_line, spawn_hud_root},
+    keymap::{Action, Keymap},
+    pipe_path::{self, Step},
+};
+
+const HIGHLIGHT_COLOR: Color = Color::srgb(1.0, 0.85, 0.1);
+
+pub struct StraightRunPlugin;
+
+impl Plugin for StraightRunPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(StraightRun {
+            cells: Vec::new(),
+            shown: false,
+        })
+        .add_systems(Startup, spawn_straight_run_text.after(spawn_hud_root))
+        .add_systems(
+            Update,
+            (
+                update_straight_run_system,
+                toggle_straight_run_system,
+                draw_straight_run_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct StraightRun {
+    /// Centers of the cells on the run, from one end to the other.
+    cells: Vec<Vec3>,
+    shown: bool,
+}
+
+#[derive(Component)]
+struct StraightRunText;
+
+/// Indices of the cells on the longest stretch of `route` left in the same
+/// direction; the first one found wins a tie.
+fn longest_run(route: &[Step]) -> Range<usize> {
+    let mut longest = 0..0;
+    let mut start = 0;
+    for i in 1..=route.len() {
+        if i < route.len() && route[i].to == route[start].to {
+            continue;
+        }
+        // Leaving every cell in `start..i` the same way lines them up with
+        // the cell the last of them leads into, if the route goes on.
+        let run = start..(i + 1).min(route.len());
+        if run.len() > longest.len() {
+            longest = run;
+        }
+        start = i;
+    }
+    longest
+}
+
+fn spawn_straight_run_text(mut commands: Commands, root: Option<Single<Entity, With<HudRoot>>>) {
+    if let Some(root) = root {
+        commands
+            .entity(*root)
+            .with_child((hud_line("", true), StraightRunText));
+    }
+}
+
+fn update_straight_run_system(
+    positions: Res<Positions>,
+    config: Res<Config>,
+    mut run: ResMut<StraightRun>,
+    mut text_query: Query<&mut Text, With<StraightRunText>>,
+) {
+    if !positions.is_changed() {
+        return;
+    }
+
+    let line = match pipe_path::ordered_path(&positions) {
+        Ok(route) => {
+            run.cells = route[longest_run(&route)]
+                .iter()
+                .map(|step| config.cell_position(step.cell))
+                .collect();
+            format!("longest straight run: {} cells", run.cells.len())
+        }
+        Err(err) => {
+            run.cells.clear();
+            format!("longest straight run: n/a ({err})")
+        }
+    };
+    for mut text in text_query.iter_mut() {
+        text.0 = line.clone();
+    }
+}
+
+fn toggle_straight_run_system(
+    key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut run: ResMut<StraightRun>,
+) {
+    if keymap.just_pressed(Action::StraightRun, &key_input) {
+        run.shown = !run.shown;
+    }
+}
+
+fn draw_straight_run_system(run: Res<StraightRun>, mut gizmos: Gizmos) {
+    if !run.shown {
+        return;
+    }
+
+    for &center in &run.cells {
+        gizmos.cuboid(
+            Transform::from_translation(center).with_scale(Vec3::splat(1.02)),
+            HIGHLIGHT_COLOR,
+        );
+    }
+    if let (Some(&first), Some(&last)) = (run.cells.first(), run.cells.last()) {
+        gizmos.line(first, last, HIGHLIGHT_COLOR);
+    }
+}
@@ -0,0 +1,118 @@
+//! Compares the solution against an intended shape loaded with
+//! `--target <file>`: cells filled outside the shape are overlaid in red,
+//! cells of the shape left empty in blue.
+//!
+//! The file lists the cells of the shape, one `x,y,z` per line. Blank lines
+//! and lines starting with `#` are ignored.
+
+use std::{collections::HashSet, fmt, fs, io, path::Path};
+
+use bevy::prelude::*;
+
+use crate::{
+    Config, Positions,
+    hud::{HudRoot, hud_line, spawn_hud_root},
+    input,
+};
+
+/// Overlay cubes are slightly larger than a cell so they enclose the block.
+const OVERLAY_SIZE: f32 = 1.04;
+const EXTRA_COLOR: Color = Color::srgba(1.0, 0.0, 0.0, 0.45);
+const MISSING_COLOR: Color = Color::srgba(0.0, 0.3, 1.0, 0.45);
+
+pub struct TargetPlugin;
+
+impl Plugin for TargetPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_target_overlay.after(spawn_hud_root));
+    }
+}
+
+/// Cells that the solution is expected to fill.
+#[derive(Resource, Debug)]
+pub struct TargetMask {
+    cells: HashSet<(usize, usize, usize)>,
+}
+
+impl TargetMask {
+    pub fn load(path: &Path) -> Result<TargetMask, TargetError> {
+        let contents = fs::read_to_string(path).map_err(TargetError::Io)?;
+        TargetMask::parse(&contents)
+    }
+
+    fn parse(contents: &str) -> Result<TargetMask, TargetError> {
+        let cells = input::parse_cell_list(contents).map_err(|line| TargetError::Malformed { line })?;
+        Ok(TargetMask { cells })
+    }
+}
+
+#[derive(Debug)]
+pub enum TargetError {
+    Io(io::Error),
+    Malformed { line: usize },
+}
+
+impl fmt::Display for TargetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TargetError::Io(err) => write!(f, "{err}"),
+            TargetError::Malformed { line } => write!(f, "line {line}: expected `x,y,z`"),
+        }
+    }
+}
+
+impl std::error::Error for TargetError {}
+
+fn spawn_target_overlay(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    positions: Res<Positions>,
+    config: Res<Config>,
+    mask: Res<TargetMask>,
+    root: Option<Single<Entity, With<HudRoot>>>,
+) {
+    let overlay = meshes.add(Cuboid::from_length(OVERLAY_SIZE));
+    let mut overlay_material = |base_color| {
+        materials.add(StandardMaterial {
+            base_color,
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        })
+    };
+    let extra_material = overlay_material(EXTRA_COLOR);
+    let missing_material = overlay_material(MISSING_COLOR);
+
+    let extra: Vec<_> = positions
+        .positions
+        .keys()
+        .filter(|cell| !mask.cells.contains(cell))
+        .collect();
+    let missing: Vec<_> = mask
+        .cells
+        .iter()
+        .filter(|cell| !positions.positions.contains_key(cell))
+        .collect();
+
+    for (cells, material) in [(&extra, &extra_material), (&missing, &missing_material)] {
+        for &&(x, y, z) in cells {
+            commands.spawn((
+                Mesh3d(overlay.clone()),
+                MeshMaterial3d(material.clone()),
+                Transform::from_translation(config.cell_position((x, y, z))),
+            ));
+        }
+    }
+
+    if let Some(root) = root {
+        commands.entity(*root).with_child(hud_line(
+            format!(
+                "target: {} cells filled outside, {} cells left empty",
+                extra.len(),
+                missing.len()
+            ),
+            true,
+        ));
+    }
+}
@@ -0,0 +1,192 @@
+//! Transparent mode: every block is drawn translucent in its own color, so
+//! the pipe network shows through the solid box. Toggled with M, or on
+//! from the start with `--transparent`. `+` and `-` fade the blocks in and
+//! out from there, between fully opaque and nearly invisible.
+//!
+//! The block materials are changed in place, so blocks respawned by
+//! `--watch` or blink pick it up with the colors they keep. Pipes stay
+//! opaque: they are drawn before the blended faces, which test against
+//! their depth without writing their own, so a pipe is never hidden
+//! behind the cube around it. Instanced blocks have their own pipeline and
+//! stay as they are.
+//!
+//! Bevy draws blended meshes back to front by the distance of each entity
+//! from the camera. Blocks are disjoint unit cubes, so that order is exact
+//! as long as only their front faces are drawn: the faces of one cube
+//! aren't sorted against each other, which is why blended blocks keep
+//! their back faces culled.
+
+use bevy::{prelude::*, render::render_resource::Face};
+
+use crate::{
+    BlockMaterials,
+    keymap::{Action, Keymap},
+};
+
+/// Opacity of the blocks when transparent mode is first turned on.
+pub const DEFAULT_ALPHA: f32 = 0.3;
+
+const ALPHA_STEP: f32 = 0.05;
+const MIN_ALPHA: f32 = 0.05;
+const MAX_ALPHA: f32 = 1.0;
+
+pub struct TransparentPlugin {
+    pub shown: bool,
+    pub alpha: f32,
+}
+
+impl Plugin for TransparentPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Transparent {
+            shown: self.shown,
+            alpha: self.alpha.clamp(MIN_ALPHA, MAX_ALPHA),
+        })
+        .add_systems(Update, (toggle_transparent_system, apply_transparent_system).chain());
+    }
+}
+
+#[derive(Resource)]
+pub struct Transparent {
+    pub shown: bool,
+    /// Opacity of the blocks while shown, kept when the mode is left.
+    pub alpha: f32,
+}
+
+fn toggle_transparent_system(
+    key_input: Option<Res<ButtonInput<KeyCode>>>,
+    keymap: Res<Keymap>,
+    mut transparent: ResMut<Transparent>,
+) {
+    let Some(key_input) = key_input else {
+        return;
+    };
+    if keymap.just_pressed(Action::Transparent, &key_input) {
+        transparent.shown = !transparent.shown;
+    }
+
+    let step = if keymap.just_pressed(Action::MoreOpaque, &key_input) {
+        ALPHA_STEP
+    } else if keymap.just_pressed(Action::LessOpaque, &key_input) {
+        -ALPHA_STEP
+    } else {
+        return;
+    };
+    // Fading from opaque blocks starts transparent mode where it was left.
+    if transparent.shown {
+        transparent.alpha = (transparent.alpha + step).clamp(MIN_ALPHA, MAX_ALPHA);
+    } else {
+        transparent.shown = true;
+    }
+    info!("Block opacity: {:.0}%", transparent.alpha * 100.0);
+}
+
+fn apply_transparent_system(
+    transparent: Res<Transparent>,
+    block_materials: Res<BlockMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // Reinserted with the new ids' materials on every respawn.
+    if !transparent.is_changed() && !block_materials.is_changed() {
+        return;
+    }
+
+    // Faded all the way in, the blocks are drawn as plain opaque ones.
+    let (alpha, alpha_mode) = if transparent.shown && transparent.alpha < MAX_ALPHA {
+        (transparent.alpha, AlphaMode::Blend)
+    } else {
+        (1.0, AlphaMode::Opaque)
+    };
+    let handles = block_materials
+        .by_id
+        .values()
+        .chain(block_materials.striped.values())
+        .chain(block_materials.by_cell.values())
+        .chain([&block_materials.fallback]);
+    for handle in handles {
+        if let Some(material) = materials.get_mut(handle) {
+            material.base_color.set_alpha(alpha);
+            material.alpha_mode = alpha_mode;
+            material.cull_mode = Some(Face::Back);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bevy::asset::AssetPlugin;
+
+    use super::*;
+    use crate::Block;
+
+    #[test]
+    fn every_block_of_a_box_is_blended_front_faces_only() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            TransparentPlugin {
+                shown: true,
+                alpha: DEFAULT_ALPHA,
+            },
+        ))
+        .init_asset::<StandardMaterial>()
+        .insert_resource(Keymap::default());
+
+        // A 2x2x2 box of blocks, the first one drawn double-sided.
+        let cells: Vec<(usize, usize, usize)> = (1..=2)
+            .flat_map(|x| (1..=2).flat_map(move |y| (1..=2).map(move |z| (x, y, z))))
+            .collect();
+        let mut materials = app.world_mut().resource_mut::<Assets<StandardMaterial>>();
+        let by_id: HashMap<String, Handle<StandardMaterial>> = (0..cells.len())
+            .map(|i| {
+                let material = StandardMaterial {
+                    cull_mode: if i == 0 { None } else { Some(Face::Back) },
+                    ..Color::hsl(i as f32 * 45.0, 0.8, 0.5).into()
+                };
+                (format!("b{i}"), materials.add(material))
+            })
+            .collect();
+        let fallback = materials.add(Color::WHITE);
+        let blocks: Vec<Entity> = cells
+            .iter()
+            .enumerate()
+            .map(|(i, &(x, y, z))| {
+                app.world_mut()
+                    .spawn((
+                        Block { x, y, z },
+                        MeshMaterial3d(by_id[&format!("b{i}")].clone()),
+                        Transform::from_xyz(x as f32, y as f32, z as f32),
+                    ))
+                    .id()
+            })
+            .collect();
+        app.insert_resource(BlockMaterials {
+            by_id,
+            striped: default(),
+            by_cell: default(),
+            fallback,
+        });
+        app.update();
+
+        let material = |app: &App, block: Entity| {
+            let handle = app.world().get::<MeshMaterial3d<StandardMaterial>>(block).unwrap();
+            app.world().resource::<Assets<StandardMaterial>>().get(handle).unwrap().clone()
+        };
+        for &block in &blocks {
+            let material = material(&app, block);
+            assert_eq!(material.alpha_mode, AlphaMode::Blend);
+            assert_eq!(material.base_color.alpha(), DEFAULT_ALPHA);
+            assert_eq!(material.cull_mode, Some(Face::Back));
+        }
+
+        app.world_mut().resource_mut::<Transparent>().shown = false;
+        app.update();
+        for &block in &blocks {
+            let material = material(&app, block);
+            assert_eq!(material.alpha_mode, AlphaMode::Opaque);
+            assert_eq!(material.base_color.alpha(), 1.0);
+        }
+    }
+}
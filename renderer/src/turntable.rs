@@ -0,0 +1,101 @@
+//! Headless turntable export: orbits the box once, rendering into an
+//! offscreen image, and saves every frame as a numbered PNG.
+
+use std::f32::consts::TAU;
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        renderer::RenderAdapter,
+        view::screenshot::save_to_disk,
+    },
+};
+
+use crate::{
+    Config, camera,
+    offscreen::{self, FrameSequence, FrameStep, PipelinesReady},
+};
+
+const TURNTABLE_WIDTH: u32 = 1280;
+const TURNTABLE_HEIGHT: u32 = 720;
+
+/// Starting yaw and fixed elevation, matching the interactive camera.
+const TURNTABLE_YAW: f32 = TAU / 8.0;
+const TURNTABLE_PITCH: f32 = TAU / 8.0;
+
+pub struct TurntablePlugin {
+    pub frames: u32,
+}
+
+impl Plugin for TurntablePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Turntable { frames: self.frames })
+            .add_systems(Startup, spawn_turntable_camera)
+            .add_systems(Update, capture_turntable_frames);
+    }
+}
+
+#[derive(Resource)]
+struct Turntable {
+    frames: u32,
+}
+
+#[derive(Component)]
+struct TurntableCamera {
+    target: Handle<Image>,
+}
+
+fn spawn_turntable_camera(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    turntable: Res<Turntable>,
+    config: Res<Config>,
+    adapter: Res<RenderAdapter>,
+) {
+    let target = offscreen::render_target(&mut images, TURNTABLE_WIDTH, TURNTABLE_HEIGHT);
+
+    commands.spawn((
+        Camera3d::default(),
+        Camera {
+            target: RenderTarget::Image(target.clone()),
+            ..default()
+        },
+        camera::projection(&config),
+        camera::supported_msaa(config.msaa, &adapter),
+        TurntableCamera { target },
+        FrameSequence::new(turntable.frames),
+    ));
+}
+
+fn capture_turntable_frames(
+    mut commands: Commands,
+    ready: Res<PipelinesReady>,
+    config: Res<Config>,
+    camera: Single<(Entity, &TurntableCamera, &mut FrameSequence, &mut Transform)>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let (entity, turntable_camera, mut sequence, mut transform) = camera.into_inner();
+    match sequence.step(&ready) {
+        FrameStep::Wait => (),
+        FrameStep::Pose(frame) => {
+            // Frame `frames` would land back on frame 0, so the sequence
+            // loops without a duplicated frame.
+            let yaw = TURNTABLE_YAW + TAU * frame as f32 / sequence.frames() as f32;
+            *transform = camera::orbit_transform(
+                camera::box_center(&config),
+                yaw,
+                TURNTABLE_PITCH,
+                camera::framing_distance(&config, config.fov),
+            );
+        }
+        FrameStep::Capture(frame) => {
+            let path = format!("turntable_{frame:04}.png");
+            offscreen::capture(&mut commands, entity, &turntable_camera.target).observe(save_to_disk(path));
+        }
+        FrameStep::Done => {
+            info!("Turntable: wrote {} frames", sequence.frames());
+            exit.send(AppExit::Success);
+        }
+    }
+}
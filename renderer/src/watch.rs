@@ -0,0 +1,139 @@
+//! `--watch`: follows the solution file while the solver writes it,
+//! re-rendering whenever it changes so the search can be watched live.
+//! The HUD says whether the state shown is still partial or final.
+
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    BlockMaterials, Config, Positions, SolutionEntities,
+    hud::{HudRoot, hud_line, spawn_hud_root},
+    input, parse_metadata, parse_sol, respawn_solution, split_models,
+};
+
+const POLL_INTERVAL: f32 = 0.5;
+
+pub struct WatchPlugin {
+    pub path: PathBuf,
+    /// Whether the state loaded at startup was still being written.
+    pub partial: bool,
+}
+
+impl Plugin for WatchPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Watch {
+            modified: modified(&self.path),
+            path: self.path.clone(),
+            timer: Timer::from_seconds(POLL_INTERVAL, TimerMode::Repeating),
+            partial: self.partial,
+            pending: None,
+        })
+        .add_systems(Startup, spawn_watch_status.after(spawn_hud_root))
+        .add_systems(Update, (poll_watched_file_system, respawn_solution_system).chain());
+    }
+}
+
+#[derive(Resource)]
+struct Watch {
+    path: PathBuf,
+    timer: Timer,
+    modified: Option<SystemTime>,
+    partial: bool,
+    /// A newly parsed state, waiting to replace the one on screen.
+    pending: Option<Positions>,
+}
+
+#[derive(Component)]
+struct WatchStatusText;
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    path.metadata().and_then(|metadata| metadata.modified()).ok()
+}
+
+fn status(watch: &Watch) -> String {
+    let state = if watch.partial { "partial" } else { "final" };
+    format!("watching {}: {state}", watch.path.display())
+}
+
+fn spawn_watch_status(
+    mut commands: Commands,
+    watch: Res<Watch>,
+    root: Option<Single<Entity, With<HudRoot>>>,
+) {
+    if let Some(root) = root {
+        commands
+            .entity(*root)
+            .with_child((hud_line(status(&watch), true), WatchStatusText));
+    }
+}
+
+fn poll_watched_file_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut watch: ResMut<Watch>,
+    mut text_query: Query<&mut Text, With<WatchStatusText>>,
+) {
+    if !watch.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let modified = modified(&watch.path);
+    if modified == watch.modified {
+        return;
+    }
+    watch.modified = modified;
+
+    // A half-written file can fail to read or parse; the next change will
+    // bring it further along, so keep showing the last good state.
+    let solution = match input::read_solution_file(&watch.path) {
+        Ok(solution) => solution,
+        Err(err) => {
+            warn!("Failed to read {}: {err}", watch.path.display());
+            return;
+        }
+    };
+    let (complete, partial) = input::complete_part(&solution);
+    // Only the latest model; the earlier ones fill the same cells
+    // differently.
+    let latest = *split_models(complete).last().unwrap();
+    match parse_sol(latest) {
+        Ok(positions) => {
+            commands.insert_resource(parse_metadata(complete));
+            watch.pending = Some(positions);
+            watch.partial = partial;
+        }
+        Err(err) => warn!("Failed to parse {}: {err}", watch.path.display()),
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.0 = status(&watch);
+    }
+}
+
+fn respawn_solution_system(
+    mut commands: Commands,
+    mut watch: ResMut<Watch>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    config: Res<Config>,
+    block_materials: Res<BlockMaterials>,
+    spawned: Query<Entity, SolutionEntities>,
+) {
+    let Some(positions) = watch.pending.take() else {
+        return;
+    };
+
+    respawn_solution(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &spawned,
+        positions,
+        &config,
+        &block_materials,
+    );
+}
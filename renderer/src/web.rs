@@ -0,0 +1,43 @@
+//! `--export-web <dir>`: writes `index.html`, a small self-contained WebGL
+//! viewer with the solution baked in, next to the solution as a bundle in
+//! `solution.json`. The page opens straight from disk in any browser, for
+//! sharing a solution with someone who doesn't have the renderer.
+//!
+//! The viewer draws the blocks in the renderer's id colors with stub
+//! pipes, orbits with the mouse and hides the blocks with H.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use serde::Serialize;
+
+use crate::{
+    Config, Positions,
+    bundle::{Bundle, BundleError},
+    id_color,
+};
+
+const VIEWER: &str = include_str!("web/viewer.html");
+
+#[derive(Serialize)]
+struct WebSolution<'a> {
+    bundle: &'a Bundle,
+    /// `#rrggbb` per block id.
+    colors: BTreeMap<&'a str, String>,
+}
+
+pub fn export(dir: &Path, config: &Config, positions: &Positions) -> Result<(), BundleError> {
+    fs::create_dir_all(dir).map_err(BundleError::Io)?;
+
+    let bundle = Bundle::new(config, positions);
+    bundle.save(&dir.join("solution.json"))?;
+
+    let colors = positions
+        .positions
+        .values()
+        .map(|block| (block.id.as_str(), id_color(&block.id, config).to_srgba().to_hex()))
+        .collect();
+    let json = serde_json::to_string(&WebSolution { bundle: &bundle, colors }).map_err(BundleError::Json)?;
+    // Ids are free text; keep them from closing the script element.
+    let page = VIEWER.replace("{{solution}}", &json.replace("</", "<\\/"));
+    fs::write(dir.join("index.html"), page).map_err(BundleError::Io)
+}
@@ -0,0 +1,96 @@
+//! Wireframe mode: blocks are drawn as cages of their triangle edges
+//! through bevy's `WireframePlugin`, their faces cleared, to peek inside a
+//! dense box without hiding layers. Pipes stay solid so the network
+//! stands out against the cages. Toggled with G. Instanced blocks are a
+//! single entity and stay as they are.
+
+use bevy::{
+    pbr::wireframe::{Wireframe, WireframeConfig},
+    prelude::*,
+};
+
+use crate::{
+    Block,
+    keymap::{Action, Keymap},
+    overlays::BlockMaterialSet,
+};
+
+pub struct WireframeModePlugin;
+
+impl Plugin for WireframeModePlugin {
+    fn build(&self, app: &mut App) {
+        // Only the blocks given a `Wireframe` are drawn as one.
+        app.insert_resource(WireframeConfig {
+            global: false,
+            default_color: Color::WHITE,
+        })
+        .insert_resource(WireframeMode { shown: false })
+        .add_systems(Startup, spawn_clear_material)
+        .add_systems(
+            Update,
+            (toggle_wireframe_system, apply_wireframe_system.in_set(BlockMaterialSet::Swaps)).chain(),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct WireframeMode {
+    pub shown: bool,
+}
+
+/// Fully transparent faces for caged blocks.
+#[derive(Resource)]
+struct ClearMaterial(Handle<StandardMaterial>);
+
+/// The material a caged block had, put back when the mode is turned off.
+#[derive(Component)]
+struct Cage(Handle<StandardMaterial>);
+
+/// Blocks not caged yet.
+type Bare = (With<Block>, Without<Cage>);
+
+fn spawn_clear_material(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    commands.insert_resource(ClearMaterial(materials.add(StandardMaterial {
+        base_color: Color::NONE,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    })));
+}
+
+fn toggle_wireframe_system(
+    key_input: Option<Res<ButtonInput<KeyCode>>>,
+    keymap: Res<Keymap>,
+    mut mode: ResMut<WireframeMode>,
+) {
+    if key_input.is_some_and(|key_input| keymap.just_pressed(Action::Wireframe, &key_input)) {
+        mode.shown = !mode.shown;
+    }
+}
+
+fn apply_wireframe_system(
+    mut commands: Commands,
+    mode: Res<WireframeMode>,
+    clear: Res<ClearMaterial>,
+    mut bare: Query<(Entity, &mut MeshMaterial3d<StandardMaterial>), Bare>,
+    mut caged: Query<(Entity, &mut Cage, &mut MeshMaterial3d<StandardMaterial>), With<Block>>,
+) {
+    // Blocks respawned while the mode is on are caged as they appear.
+    if mode.shown {
+        for (entity, mut material) in bare.iter_mut() {
+            commands.entity(entity).insert((Wireframe, Cage(material.0.clone())));
+            material.0 = clear.0.clone();
+        }
+        // Overlays resolved under the cage are kept for when it comes off.
+        for (_, mut cage, mut material) in caged.iter_mut() {
+            if material.0 != clear.0 {
+                cage.0 = std::mem::replace(&mut material.0, clear.0.clone());
+            }
+        }
+    } else if mode.is_changed() {
+        for (entity, cage, mut material) in caged.iter_mut() {
+            material.0 = cage.0.clone();
+            commands.entity(entity).remove::<(Wireframe, Cage)>();
+        }
+    }
+}